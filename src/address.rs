@@ -0,0 +1,107 @@
+//! Ethereum address type used when validating and comparing gravity addresses
+use std::{fmt, str::FromStr};
+
+use ocular::cosmrs::AccountId;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use crate::{bail, Error, Result};
+
+/// The name the gravity module registers its module account under
+pub const GRAVITY_MODULE_NAME: &str = "gravity";
+/// The bech32 account prefix used by Sommelier
+pub const SOMMELIER_ACCOUNT_PREFIX: &str = "somm";
+
+/// Derives the bech32 address of a module account with the standard cosmos derivation, the first 20 bytes of
+/// `sha256(module_name)`
+pub fn module_account_address(module_name: &str, prefix: &str) -> Result<String> {
+    let hash = Sha256::digest(module_name.as_bytes());
+    match AccountId::new(prefix, &hash[..20]) {
+        Ok(account) => Ok(account.to_string()),
+        Err(e) => bail!("failed to derive module account address for {}: {}", module_name, e),
+    }
+}
+
+/// A 20 byte Ethereum address. Parsing accepts hex with or without a `0x` prefix, in any casing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EthAddress([u8; 20]);
+
+impl EthAddress {
+    pub fn from_bytes(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Returns the EIP-55 mixed case checksum encoding of the address, with a `0x` prefix
+    pub fn to_checksum(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = Keccak256::digest(lower.as_bytes());
+
+        let mut checksummed = String::with_capacity(42);
+        checksummed.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+            if nibble >= 8 {
+                checksummed.push(c.to_ascii_uppercase());
+            } else {
+                checksummed.push(c);
+            }
+        }
+
+        checksummed
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let stripped = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .unwrap_or(s);
+        if stripped.len() != 40 {
+            bail!(
+                "invalid ethereum address {}: expected 40 hex characters, got {}",
+                s,
+                stripped.len()
+            )
+        }
+
+        let mut bytes = [0u8; 20];
+        if let Err(e) = hex::decode_to_slice(stripped, &mut bytes) {
+            bail!("invalid ethereum address {}: {}", s, e)
+        }
+
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for EthAddress {
+    /// Formats the address as lowercase hex with a `0x` prefix
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_account_address_matches_chain() {
+        // The gravity module account on Sommelier mainnet
+        assert_eq!(
+            module_account_address(GRAVITY_MODULE_NAME, SOMMELIER_ACCOUNT_PREFIX).unwrap(),
+            "somm16n3lc7cywa68mg50qhp847034w88pntq22vzye"
+        );
+        // The distribution module account on the Cosmos Hub, as a check of the derivation itself
+        assert_eq!(
+            module_account_address("distribution", "cosmos").unwrap(),
+            "cosmos1jv65s3grqf6v6jl3dp4t6c9t9rk99cd88lyufl"
+        );
+    }
+}
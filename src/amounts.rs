@@ -0,0 +1,580 @@
+//! Helpers for working with the amounts carried by gravity responses
+use std::fmt;
+
+use gravity_proto::gravity::{BatchTx, Erc20Token, SendToEthereum};
+use ocular::cosmrs::{AccountId, Coin, Denom};
+use primitive_types::U256;
+
+#[cfg(feature = "query")]
+use crate::extension::SommGravityExt;
+use crate::{address::EthAddress, bail, Result};
+
+/// Parses the integer amount string of an [`Erc20Token`]
+pub fn parse_erc20_amount(token: &Erc20Token) -> Result<u128> {
+    match token.amount.parse::<u128>() {
+        Ok(amount) => Ok(amount),
+        Err(e) => bail!(
+            "invalid amount {} for token {}: {}",
+            token.amount,
+            token.contract,
+            e
+        ),
+    }
+}
+
+/// Converts an integer amount between representations with different decimals, such as 18 decimal ERC20 units
+/// and 6 decimal cosmos units, so the represented value is unchanged. Errors if scaling up overflows, or if
+/// scaling down would drop non-zero digits rather than silently truncating them.
+pub fn convert_units(amount: u128, from_decimals: u32, to_decimals: u32) -> Result<u128> {
+    // Zero is zero at any scale, even one whose factor doesn't fit in a u128
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    if to_decimals >= from_decimals {
+        let factor = match 10u128.checked_pow(to_decimals - from_decimals) {
+            Some(factor) => factor,
+            None => bail!("cannot scale from {} to {} decimals", from_decimals, to_decimals),
+        };
+        match amount.checked_mul(factor) {
+            Some(converted) => Ok(converted),
+            None => bail!(
+                "{} overflows when converted from {} to {} decimals",
+                amount,
+                from_decimals,
+                to_decimals
+            ),
+        }
+    } else {
+        // Any factor beyond u128 divides every non-zero amount with a remainder
+        let factor = match 10u128.checked_pow(from_decimals - to_decimals) {
+            Some(factor) => factor,
+            None => bail!(
+                "{} loses precision when converted from {} to {} decimals",
+                amount,
+                from_decimals,
+                to_decimals
+            ),
+        };
+        if amount % factor != 0 {
+            bail!(
+                "{} loses precision when converted from {} to {} decimals",
+                amount,
+                from_decimals,
+                to_decimals
+            )
+        }
+
+        Ok(amount / factor)
+    }
+}
+
+/// A typed [`Erc20Token`], as carried by sends and batches. Amounts are `uint256` on Ethereum, so they are held
+/// as a [`U256`] rather than the `u128` cosmos coins use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Erc20Amount {
+    pub contract: EthAddress,
+    pub amount: U256,
+}
+
+impl Erc20Amount {
+    /// Returns the amount as a `u128`, erroring if it doesn't fit
+    pub fn amount_u128(&self) -> Result<u128> {
+        if self.amount > U256::from(u128::MAX) {
+            bail!("amount {} of {} exceeds u128", self.amount, self.contract)
+        }
+
+        Ok(self.amount.as_u128())
+    }
+
+    /// Formats the amount as a decimal string with `decimals` fractional digits, as [`format_amount`] does
+    pub fn format(&self, decimals: u32) -> String {
+        format_digits(&self.amount.to_string(), decimals)
+    }
+
+    /// Converts the amount to a [`Coin`] in the cosmos denom the module maps the contract to. Errors if the
+    /// amount exceeds `u128`, the largest cosmos coin amount.
+    #[cfg(feature = "query")]
+    pub async fn to_coin<T: SommGravityExt>(&self, client: &T) -> Result<Coin> {
+        let amount = self.amount_u128()?;
+        let denom = client.query_erc20_to_denom(self.contract.to_checksum()).await?;
+        let denom = match denom.parse::<Denom>() {
+            Ok(denom) => denom,
+            Err(e) => bail!("invalid denom {} for {}: {}", denom, self.contract, e),
+        };
+
+        Ok(Coin { denom, amount })
+    }
+}
+
+impl TryFrom<&Erc20Token> for Erc20Amount {
+    type Error = crate::Error;
+
+    fn try_from(token: &Erc20Token) -> Result<Self> {
+        let contract = token.contract.parse::<EthAddress>()?;
+        let amount = match U256::from_dec_str(&token.amount) {
+            Ok(amount) => amount,
+            Err(e) => bail!("invalid amount {} for token {}: {:?}", token.amount, token.contract, e),
+        };
+
+        Ok(Self { contract, amount })
+    }
+}
+
+impl From<Erc20Amount> for Erc20Token {
+    fn from(amount: Erc20Amount) -> Self {
+        Erc20Token {
+            contract: amount.contract.to_checksum(),
+            amount: amount.amount.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Erc20Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.contract)
+    }
+}
+
+/// Formats an integer token amount as a decimal string with `decimals` fractional digits, trimming trailing
+/// zeros. For example `1234500` with 6 decimals is `1.2345`.
+pub fn format_amount(amount: u128, decimals: u32) -> String {
+    format_digits(&amount.to_string(), decimals)
+}
+
+/// Inserts the decimal point `decimals` digits from the end of an integer's base 10 `digits`
+fn format_digits(digits: &str, decimals: u32) -> String {
+    if decimals == 0 {
+        return digits.to_string();
+    }
+
+    let digits = format!("{:0>width$}", digits, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Checks that `denom` is a valid cosmos denom. Every denom must be 3 to 128 characters, start with a letter
+/// and otherwise contain only letters, digits and `/:._-`. Denoms with these prefixes must also match their
+/// form:
+///
+/// * `ibc/<hash>`: an IBC voucher, where the hash is 64 hex digits
+/// * `factory/<creator>/<subdenom>`: a token factory denom, where the creator is a bech32 address and the
+///   subdenom is non-empty
+/// * `gravity0x<contract>`: the denom gravity mints for an Ethereum originated token, where the contract is a
+///   20 byte hex address. Other denoms that merely start with `gravity` are accepted as native denoms.
+pub fn validate_denom(denom: &str) -> Result<()> {
+    if denom.len() < 3 || denom.len() > 128 {
+        bail!("invalid denom {}: must be 3 to 128 characters", denom)
+    }
+    if !denom.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        bail!("invalid denom {}: must start with a letter", denom)
+    }
+    if let Some(c) = denom
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !"/:._-".contains(*c))
+    {
+        bail!("invalid denom {}: unexpected character {:?}", denom, c)
+    }
+
+    if let Some(hash) = denom.strip_prefix("ibc/") {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("invalid denom {}: ibc denoms must be ibc/ followed by a 64 digit hex hash", denom)
+        }
+    } else if let Some(rest) = denom.strip_prefix("factory/") {
+        let (creator, subdenom) = match rest.split_once('/') {
+            Some(parts) => parts,
+            None => bail!("invalid denom {}: factory denoms must be factory/<creator>/<subdenom>", denom),
+        };
+        if let Err(e) = creator.parse::<AccountId>() {
+            bail!("invalid denom {}: invalid factory creator {}: {}", denom, creator, e)
+        }
+        if subdenom.is_empty() {
+            bail!("invalid denom {}: factory subdenom must not be empty", denom)
+        }
+    } else if let Some(contract) = denom.strip_prefix("gravity0x") {
+        if let Err(e) = contract.parse::<EthAddress>() {
+            bail!(
+                "invalid denom {}: gravity denoms must be gravity0x followed by a contract address: {}",
+                denom,
+                e
+            )
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes a bridge fee of `bps` basis points of `amount`, in the same denom, rounding half up. For example
+/// 10 bps of `1000005usomm` is `1000usomm`. `bps` must be at most 10000, a fee equal to the amount. Pass the
+/// result as the `bridge_fee` of [`SommGravity::SendToEthereum`](crate::msgs::SommGravity::SendToEthereum).
+pub fn bridge_fee_from_bps(amount: &Coin, bps: u32) -> Result<Coin> {
+    if bps > 10_000 {
+        bail!("bridge fee of {} bps exceeds 10000 bps", bps)
+    }
+
+    // Split the amount so the multiplication can't overflow for any u128 amount
+    let bps = bps as u128;
+    let whole = amount.amount / 10_000 * bps;
+    let remainder = (amount.amount % 10_000 * bps + 5_000) / 10_000;
+
+    Ok(Coin {
+        denom: amount.denom.clone(),
+        amount: whole + remainder,
+    })
+}
+
+/// Returns `amount + bridge_fee` for a send, denominated in the `gravity0x...` denom gravity uses for
+/// Ethereum originated tokens. Errors if the amount and fee are for different contracts or the sum overflows.
+///
+/// The denom is derived from the contract without a query, so it is only correct for Ethereum originated
+/// tokens. A cosmos originated token such as `usomm` is bridged through an ERC20 the module deployed for it and
+/// keeps its own denom, which can't be told apart offline. When the token's origin isn't known, use
+/// [`total_amount_plus_fee_in`] with the denom from
+/// [`SommGravityExt::send_denom`](crate::extension::SommGravityExt::send_denom), or
+/// [`SommGravityExt::send_total`](crate::extension::SommGravityExt::send_total).
+pub fn total_amount_plus_fee(send: &SendToEthereum) -> Result<Coin> {
+    let (contract, total) = send_total(send)?;
+
+    Ok(Coin {
+        denom: gravity_denom(&contract)?,
+        amount: total,
+    })
+}
+
+/// Returns `amount + bridge_fee` for a send in an explicitly provided denom, such as one resolved with
+/// [`SommGravityExt::send_denom`](crate::extension::SommGravityExt::send_denom).
+pub fn total_amount_plus_fee_in(send: &SendToEthereum, denom: &str) -> Result<Coin> {
+    let (_, total) = send_total(send)?;
+    let denom = match denom.parse::<Denom>() {
+        Ok(denom) => denom,
+        Err(e) => bail!("invalid denom {}: {}", denom, e),
+    };
+
+    Ok(Coin {
+        denom,
+        amount: total,
+    })
+}
+
+/// Sums `amount + bridge_fee` over all `sends`, which must all be for the same contract. See
+/// [`total_amount_plus_fee`] for the denom of the result, which is only correct for Ethereum originated tokens;
+/// use [`sum_amounts_plus_fees_in`] otherwise.
+pub fn sum_amounts_plus_fees(sends: &[SendToEthereum]) -> Result<Coin> {
+    let (contract, total) = sum_sends(sends)?;
+
+    Ok(Coin {
+        denom: gravity_denom(&contract)?,
+        amount: total,
+    })
+}
+
+/// Same as [`sum_amounts_plus_fees`] in an explicitly provided denom, such as one resolved with
+/// [`SommGravityExt::send_denom`](crate::extension::SommGravityExt::send_denom).
+pub fn sum_amounts_plus_fees_in(sends: &[SendToEthereum], denom: &str) -> Result<Coin> {
+    let (_, total) = sum_sends(sends)?;
+    let denom = match denom.parse::<Denom>() {
+        Ok(denom) => denom,
+        Err(e) => bail!("invalid denom {}: {}", denom, e),
+    };
+
+    Ok(Coin {
+        denom,
+        amount: total,
+    })
+}
+
+/// Returns the shared token contract of `sends` and the sum of their `amount + bridge_fee`
+fn sum_sends(sends: &[SendToEthereum]) -> Result<(String, u128)> {
+    let mut contract: Option<String> = None;
+    let mut total: u128 = 0;
+    for send in sends {
+        let (send_contract, send_total) = send_total(send)?;
+        match contract.as_ref() {
+            Some(c) if !c.eq_ignore_ascii_case(&send_contract) => bail!(
+                "send {} is for contract {} but previous sends are for {}",
+                send.id,
+                send_contract,
+                c
+            ),
+            Some(_) => (),
+            None => contract = Some(send_contract),
+        }
+        total = match total.checked_add(send_total) {
+            Some(total) => total,
+            None => bail!("total overflowed at send {}", send.id),
+        };
+    }
+
+    match contract {
+        Some(contract) => Ok((contract, total)),
+        None => bail!("no sends to sum"),
+    }
+}
+
+/// Sums the bridge fees of every send in a batch, the fees a relayer earns for relaying it, in `denom`, the
+/// cosmos denom of the batch's token contract as resolved with `query_erc20_to_denom`. Errors if a send's fee
+/// is for a different contract than the batch or the sum overflows.
+pub fn batch_total_fees(batch: &BatchTx, denom: &str) -> Result<Coin> {
+    let mut total: u128 = 0;
+    for send in batch.transactions.iter() {
+        let fee = match send.erc20_fee.as_ref() {
+            Some(fee) if !fee.contract.eq_ignore_ascii_case(&batch.token_contract) => bail!(
+                "send {} fee contract {} does not match batch token contract {}",
+                send.id,
+                fee.contract,
+                batch.token_contract
+            ),
+            Some(fee) => parse_erc20_amount(fee)?,
+            None => 0,
+        };
+        total = match total.checked_add(fee) {
+            Some(total) => total,
+            None => bail!("batch {} fees overflowed at send {}", batch.batch_nonce, send.id),
+        };
+    }
+
+    let denom = match denom.parse::<Denom>() {
+        Ok(denom) => denom,
+        Err(e) => bail!("invalid denom {}: {}", denom, e),
+    };
+
+    Ok(Coin {
+        denom,
+        amount: total,
+    })
+}
+
+/// Returns the token contract and `amount + bridge_fee` of a send
+fn send_total(send: &SendToEthereum) -> Result<(String, u128)> {
+    let token = match send.erc20_token.as_ref() {
+        Some(token) => token,
+        None => bail!("send {} has no erc20 token", send.id),
+    };
+    let amount = parse_erc20_amount(token)?;
+    let fee = match send.erc20_fee.as_ref() {
+        Some(fee) if !fee.contract.eq_ignore_ascii_case(&token.contract) => bail!(
+            "send {} fee contract {} does not match token contract {}",
+            send.id,
+            fee.contract,
+            token.contract
+        ),
+        Some(fee) => parse_erc20_amount(fee)?,
+        None => 0,
+    };
+
+    match amount.checked_add(fee) {
+        Some(total) => Ok((token.contract.clone(), total)),
+        None => bail!("amount plus fee overflowed for send {}", send.id),
+    }
+}
+
+/// Derives the `gravity0x...` denom of an Ethereum originated token. Not valid for cosmos originated tokens,
+/// whose denom must be resolved with `query_erc20_to_denom`.
+fn gravity_denom(contract: &str) -> Result<Denom> {
+    let denom = format!("gravity{}", contract);
+    match denom.parse::<Denom>() {
+        Ok(denom) => Ok(denom),
+        Err(e) => bail!("invalid denom {}: {}", denom, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_AND_A_HALF_6: u128 = 1_500_000;
+    const ONE_AND_A_HALF_18: u128 = 1_500_000_000_000_000_000;
+
+    #[test]
+    fn convert_units_scales_up() {
+        assert_eq!(convert_units(ONE_AND_A_HALF_6, 6, 18).unwrap(), ONE_AND_A_HALF_18);
+    }
+
+    #[test]
+    fn convert_units_scales_down() {
+        assert_eq!(convert_units(ONE_AND_A_HALF_18, 18, 6).unwrap(), ONE_AND_A_HALF_6);
+    }
+
+    #[test]
+    fn convert_units_same_decimals() {
+        assert_eq!(convert_units(ONE_AND_A_HALF_6, 6, 6).unwrap(), ONE_AND_A_HALF_6);
+    }
+
+    #[test]
+    fn convert_units_rejects_overflow() {
+        let err = convert_units(u128::MAX, 6, 18).unwrap_err();
+        assert!(err.to_string().contains("overflows"), "{}", err);
+    }
+
+    #[test]
+    fn convert_units_rejects_precision_loss() {
+        let err = convert_units(ONE_AND_A_HALF_18 + 1, 18, 6).unwrap_err();
+        assert!(err.to_string().contains("loses precision"), "{}", err);
+    }
+
+    #[test]
+    fn convert_units_factor_overflow() {
+        // 10^39 exceeds u128, so the scaling factor itself can't be computed
+        assert!(convert_units(1, 0, 39).is_err());
+
+        let err = convert_units(1, 39, 0).unwrap_err();
+        assert!(err.to_string().contains("loses precision"), "{}", err);
+    }
+
+    #[test]
+    fn validate_denom_accepts_valid_forms() {
+        let ibc = format!("ibc/{}", "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2");
+        let long = format!("u{}", "a".repeat(127));
+        let accepted = [
+            "usomm",
+            "abc",
+            long.as_str(),
+            "erc20/0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "pool:1.share_token-a",
+            ibc.as_str(),
+            "factory/somm1qyqszqgpqyqszqgpqyqszqgpqyqszqgp70wjuk/ustable",
+            "factory/somm1qyqszqgpqyqszqgpqyqszqgpqyqszqgp70wjuk/nested/sub",
+            "gravity0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            "gravity0xA0b86991c6218b36c1d19d4a2e9eB0cE3606eB48",
+            // Native denoms that merely start with "gravity"
+            "gravitytoken",
+            "gravity",
+            "ugravity0x",
+        ];
+        for denom in accepted {
+            assert!(validate_denom(denom).is_ok(), "{}: {:?}", denom, validate_denom(denom));
+        }
+    }
+
+    #[test]
+    fn validate_denom_rejects_invalid_forms() {
+        let too_long = format!("u{}", "a".repeat(128));
+        let rejected = [
+            ("", "3 to 128"),
+            ("us", "3 to 128"),
+            (too_long.as_str(), "3 to 128"),
+            ("1somm", "start with a letter"),
+            ("/somm", "start with a letter"),
+            ("u somm", "unexpected character"),
+            ("usomm!", "unexpected character"),
+            ("ibc/27394FB092D2ECCD", "64 digit hex"),
+            (
+                "ibc/Z7394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+                "64 digit hex",
+            ),
+            ("factory/somm1qyqszqgpqyqszqgpqyqszqgpqyqszqgp70wjuk", "factory/<creator>/<subdenom>"),
+            ("factory/notbech32/ustable", "invalid factory creator"),
+            ("factory/somm1qyqszqgpqyqszqgpqyqszqgpqyqszqgp70wjuk/", "subdenom must not be empty"),
+            ("gravity0x1234", "gravity denoms"),
+            ("gravity0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb4z", "gravity denoms"),
+        ];
+        for (denom, expected) in rejected {
+            let err = validate_denom(denom).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", denom, err);
+        }
+    }
+
+    #[test]
+    fn convert_units_zero_at_any_scale() {
+        assert_eq!(convert_units(0, 0, 39).unwrap(), 0);
+        assert_eq!(convert_units(0, 39, 0).unwrap(), 0);
+        assert_eq!(convert_units(0, 6, 18).unwrap(), 0);
+    }
+
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn send(id: u64, contract: &str, amount: u128, fee: Option<(&str, u128)>) -> SendToEthereum {
+        SendToEthereum {
+            id,
+            erc20_token: Some(Erc20Token {
+                contract: contract.to_string(),
+                amount: amount.to_string(),
+            }),
+            erc20_fee: fee.map(|(contract, amount)| Erc20Token {
+                contract: contract.to_string(),
+                amount: amount.to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn total_amount_plus_fee_in_gravity_denom() {
+        let total = total_amount_plus_fee(&send(1, USDC, 1_000, Some((USDC, 10)))).unwrap();
+        assert_eq!(total.amount, 1_010);
+        assert_eq!(total.denom.to_string(), format!("gravity{}", USDC));
+    }
+
+    #[test]
+    fn total_amount_plus_fee_without_fee() {
+        assert_eq!(total_amount_plus_fee(&send(1, USDC, 1_000, None)).unwrap().amount, 1_000);
+    }
+
+    #[test]
+    fn total_amount_plus_fee_rejects_mismatched_fee_contract() {
+        let err = total_amount_plus_fee(&send(1, USDC, 1_000, Some((WETH, 10)))).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+    }
+
+    #[test]
+    fn total_amount_plus_fee_accepts_fee_contract_in_other_casing() {
+        let total = total_amount_plus_fee(&send(1, USDC, 1_000, Some((&USDC.to_uppercase().replace("0X", "0x"), 10))));
+        assert_eq!(total.unwrap().amount, 1_010);
+    }
+
+    #[test]
+    fn total_amount_plus_fee_rejects_overflow() {
+        let err = total_amount_plus_fee(&send(1, USDC, u128::MAX, Some((USDC, 1)))).unwrap_err();
+        assert!(err.to_string().contains("overflowed"), "{}", err);
+    }
+
+    #[test]
+    fn total_amount_plus_fee_in_uses_given_denom() {
+        let total = total_amount_plus_fee_in(&send(1, USDC, 1_000, Some((USDC, 10))), "usomm").unwrap();
+        assert_eq!(total.amount, 1_010);
+        assert_eq!(total.denom.to_string(), "usomm");
+    }
+
+    #[test]
+    fn sum_amounts_plus_fees_sums_sends() {
+        let sends = vec![send(1, USDC, 1_000, Some((USDC, 10))), send(2, USDC, 2_000, None)];
+        let total = sum_amounts_plus_fees(&sends).unwrap();
+        assert_eq!(total.amount, 3_010);
+        assert_eq!(total.denom.to_string(), format!("gravity{}", USDC));
+    }
+
+    #[test]
+    fn sum_amounts_plus_fees_rejects_empty_input() {
+        let err = sum_amounts_plus_fees(&[]).unwrap_err();
+        assert!(err.to_string().contains("no sends"), "{}", err);
+        assert!(sum_amounts_plus_fees_in(&[], "usomm").is_err());
+    }
+
+    #[test]
+    fn sum_amounts_plus_fees_rejects_mixed_contracts() {
+        let sends = vec![send(1, USDC, 1_000, None), send(2, WETH, 2_000, None)];
+        let err = sum_amounts_plus_fees(&sends).unwrap_err();
+        assert!(err.to_string().contains("previous sends"), "{}", err);
+    }
+
+    #[test]
+    fn sum_amounts_plus_fees_rejects_overflow() {
+        let sends = vec![send(1, USDC, u128::MAX - 1, None), send(2, USDC, 1, Some((USDC, 1)))];
+        let err = sum_amounts_plus_fees(&sends).unwrap_err();
+        assert!(err.to_string().contains("overflowed at send 2"), "{}", err);
+    }
+
+    #[test]
+    fn sum_amounts_plus_fees_in_uses_given_denom() {
+        let sends = vec![send(1, USDC, 1_000, Some((USDC, 10))), send(2, USDC, 2_000, Some((USDC, 20)))];
+        let total = sum_amounts_plus_fees_in(&sends, "usomm").unwrap();
+        assert_eq!(total.amount, 3_030);
+        assert_eq!(total.denom.to_string(), "usomm");
+    }
+}
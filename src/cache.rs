@@ -0,0 +1,40 @@
+//! Process wide caches for query results that never change for a given endpoint
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::address::EthAddress;
+
+/// A map from `(endpoint, key)` to a cached value. Only values that are immutable on chain, such as token
+/// mappings, should be stored here since entries are never invalidated.
+pub(crate) struct EndpointCache<V> {
+    inner: OnceLock<Mutex<HashMap<(String, String), V>>>,
+}
+
+impl<V: Clone> EndpointCache<V> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            inner: OnceLock::new(),
+        }
+    }
+
+    fn map(&self) -> &Mutex<HashMap<(String, String), V>> {
+        self.inner.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    pub(crate) fn get(&self, endpoint: &str, key: &str) -> Option<V> {
+        let map = self.map().lock().unwrap_or_else(|e| e.into_inner());
+        map.get(&(endpoint.to_string(), key.to_string())).cloned()
+    }
+
+    pub(crate) fn insert(&self, endpoint: &str, key: &str, value: V) {
+        let mut map = self.map().lock().unwrap_or_else(|e| e.into_inner());
+        map.insert((endpoint.to_string(), key.to_string()), value);
+    }
+}
+
+pub(crate) static ERC20_TO_DENOM: EndpointCache<String> = EndpointCache::new();
+pub(crate) static ERC20_DECIMALS: EndpointCache<u32> = EndpointCache::new();
+pub(crate) static GRAVITY_ID: EndpointCache<[u8; 32]> = EndpointCache::new();
+pub(crate) static BRIDGE_CONTRACT: EndpointCache<EthAddress> = EndpointCache::new();
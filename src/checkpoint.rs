@@ -0,0 +1,238 @@
+//! Checkpoint computation for gravity's outgoing txs
+use gravity_proto::gravity::{BatchTx, ContractCallTx, Erc20Token, SignerSetTx};
+use sha3::{Digest, Keccak256};
+
+use crate::{address::EthAddress, bail, Result};
+
+/// Converts the `gravity_id` param into the fixed 32 byte form used in checkpoints: the UTF-8 bytes of the id,
+/// right padded with zeros. Ids longer than 32 bytes are rejected, matching the module.
+pub fn parse_gravity_id(gravity_id: &str) -> Result<[u8; 32]> {
+    let bytes = gravity_id.as_bytes();
+    if bytes.is_empty() {
+        bail!("gravity id must not be empty")
+    }
+    if bytes.len() > 32 {
+        bail!(
+            "gravity id {} is {} bytes, longer than 32",
+            gravity_id,
+            bytes.len()
+        )
+    }
+
+    let mut id = [0u8; 32];
+    id[..bytes.len()].copy_from_slice(bytes);
+
+    Ok(id)
+}
+
+/// The `transactionBatch` method name as a bytes32, as `Gravity.sol` hashes it into batch checkpoints
+const BATCH_METHOD_NAME: [u8; 32] = method_name(b"transactionBatch");
+/// The `checkpoint` method name as a bytes32, as `Gravity.sol` hashes it into signer set checkpoints
+const SIGNER_SET_METHOD_NAME: [u8; 32] = method_name(b"checkpoint");
+/// The `logicCall` method name as a bytes32, as `Gravity.sol` hashes it into contract call checkpoints
+const CONTRACT_CALL_METHOD_NAME: [u8; 32] = method_name(b"logicCall");
+
+/// Right pads a method name to the bytes32 form `Gravity.sol` hashes
+const fn method_name(name: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    let mut i = 0;
+    while i < name.len() {
+        word[i] = name[i];
+        i += 1;
+    }
+
+    word
+}
+
+/// Computes the checkpoint orchestrators sign for a signer set: the keccak256 of the ABI encoded
+/// `(gravity_id, "checkpoint", nonce, addresses, powers)`, matching `Gravity.sol`'s `makeCheckpoint`. Signers
+/// are encoded in the order the module stores them.
+pub fn signer_set_checkpoint(gravity_id: &[u8; 32], signer_set: &SignerSetTx) -> Result<[u8; 32]> {
+    let mut addresses = Vec::with_capacity(signer_set.signers.len());
+    let mut powers = Vec::with_capacity(signer_set.signers.len());
+    for signer in signer_set.signers.iter() {
+        let address: EthAddress = signer.ethereum_address.parse()?;
+        addresses.push(address_word(&address));
+        powers.push(u64_word(signer.power));
+    }
+
+    let encoded = abi_encode(&[
+        Token::Word(*gravity_id),
+        Token::Word(SIGNER_SET_METHOD_NAME),
+        Token::Word(u64_word(signer_set.nonce)),
+        Token::Array(addresses),
+        Token::Array(powers),
+    ]);
+
+    Ok(keccak256(&encoded))
+}
+
+/// Computes the checkpoint orchestrators sign for a contract call: the keccak256 of the ABI encoded
+/// `(gravity_id, "logicCall", token amounts, token contracts, fee amounts, fee contracts, address, payload,
+/// timeout, invalidation scope, invalidation nonce)`, matching `Gravity.sol`'s `submitLogicCall`. The
+/// invalidation scope is right padded to a bytes32.
+pub fn contract_call_checkpoint(gravity_id: &[u8; 32], call: &ContractCallTx) -> Result<[u8; 32]> {
+    let (token_amounts, token_contracts) = token_words(&call.tokens)?;
+    let (fee_amounts, fee_contracts) = token_words(&call.fees)?;
+    let address: EthAddress = call.address.parse()?;
+    if call.invalidation_scope.len() > 32 {
+        bail!(
+            "invalidation scope is {} bytes, longer than 32",
+            call.invalidation_scope.len()
+        )
+    }
+    let mut scope = [0u8; 32];
+    scope[..call.invalidation_scope.len()].copy_from_slice(&call.invalidation_scope);
+
+    let encoded = abi_encode(&[
+        Token::Word(*gravity_id),
+        Token::Word(CONTRACT_CALL_METHOD_NAME),
+        Token::Array(token_amounts),
+        Token::Array(token_contracts),
+        Token::Array(fee_amounts),
+        Token::Array(fee_contracts),
+        Token::Word(address_word(&address)),
+        Token::Bytes(&call.payload),
+        Token::Word(u64_word(call.timeout)),
+        Token::Word(scope),
+        Token::Word(u64_word(call.invalidation_nonce)),
+    ]);
+
+    Ok(keccak256(&encoded))
+}
+
+/// Computes the checkpoint orchestrators sign for a batch: the keccak256 of the ABI encoded
+/// `(gravity_id, "transactionBatch", amounts, destinations, fees, batch_nonce, token_contract, timeout)`, matching
+/// `Gravity.sol`'s `submitBatch`.
+pub fn batch_checkpoint(gravity_id: &[u8; 32], batch: &BatchTx) -> Result<[u8; 32]> {
+    let mut amounts = Vec::with_capacity(batch.transactions.len());
+    let mut destinations = Vec::with_capacity(batch.transactions.len());
+    let mut fees = Vec::with_capacity(batch.transactions.len());
+    for (i, send) in batch.transactions.iter().enumerate() {
+        let amount = match &send.erc20_token {
+            Some(token) => uint_word(&token.amount)?,
+            None => bail!("batch {} send at index {} has no erc20 token", batch.batch_nonce, i),
+        };
+        let fee = match &send.erc20_fee {
+            Some(fee) => uint_word(&fee.amount)?,
+            None => [0u8; 32],
+        };
+        let destination: EthAddress = send.ethereum_recipient.parse()?;
+
+        amounts.push(amount);
+        destinations.push(address_word(&destination));
+        fees.push(fee);
+    }
+    let token_contract: EthAddress = batch.token_contract.parse()?;
+
+    let encoded = abi_encode(&[
+        Token::Word(*gravity_id),
+        Token::Word(BATCH_METHOD_NAME),
+        Token::Array(amounts),
+        Token::Array(destinations),
+        Token::Array(fees),
+        Token::Word(u64_word(batch.batch_nonce)),
+        Token::Word(address_word(&token_contract)),
+        Token::Word(u64_word(batch.timeout)),
+    ]);
+
+    Ok(keccak256(&encoded))
+}
+
+pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&Keccak256::digest(data));
+
+    out
+}
+
+/// Splits tokens into their ABI encoded amounts and contracts
+fn token_words(tokens: &[Erc20Token]) -> Result<(Vec<[u8; 32]>, Vec<[u8; 32]>)> {
+    let mut amounts = Vec::with_capacity(tokens.len());
+    let mut contracts = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let contract: EthAddress = token.contract.parse()?;
+        amounts.push(uint_word(&token.amount)?);
+        contracts.push(address_word(&contract));
+    }
+
+    Ok((amounts, contracts))
+}
+
+/// An ABI value. Words are static and encoded in place, arrays and bytes are dynamic and encoded in the tail.
+enum Token<'a> {
+    Word([u8; 32]),
+    Array(Vec<[u8; 32]>),
+    Bytes(&'a [u8]),
+}
+
+/// ABI encodes a tuple of tokens the way Solidity's `abi.encode` does
+fn abi_encode(tokens: &[Token]) -> Vec<u8> {
+    let mut head = Vec::with_capacity(tokens.len() * 32);
+    let mut tail = Vec::new();
+    let head_len = tokens.len() * 32;
+
+    for token in tokens {
+        match token {
+            Token::Word(word) => head.extend_from_slice(word),
+            Token::Array(words) => {
+                head.extend_from_slice(&u64_word((head_len + tail.len()) as u64));
+                tail.extend_from_slice(&u64_word(words.len() as u64));
+                for word in words {
+                    tail.extend_from_slice(word);
+                }
+            }
+            Token::Bytes(bytes) => {
+                head.extend_from_slice(&u64_word((head_len + tail.len()) as u64));
+                tail.extend_from_slice(&u64_word(bytes.len() as u64));
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len() + (32 - bytes.len() % 32) % 32, 0);
+            }
+        }
+    }
+
+    head.extend_from_slice(&tail);
+
+    head
+}
+
+fn u64_word(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+
+    word
+}
+
+fn address_word(address: &EthAddress) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address.as_bytes());
+
+    word
+}
+
+/// Parses a base 10 integer string, as the module stores token amounts, into a big endian uint256 word
+fn uint_word(value: &str) -> Result<[u8; 32]> {
+    if value.is_empty() {
+        bail!("invalid amount: empty string")
+    }
+
+    let mut word = [0u8; 32];
+    for c in value.chars() {
+        let digit = match c.to_digit(10) {
+            Some(d) => d,
+            None => bail!("invalid amount {}: not a base 10 integer", value),
+        };
+
+        let mut carry = digit;
+        for byte in word.iter_mut().rev() {
+            let v = *byte as u32 * 10 + carry;
+            *byte = v as u8;
+            carry = v >> 8;
+        }
+        if carry != 0 {
+            bail!("invalid amount {}: overflows uint256", value)
+        }
+    }
+
+    Ok(word)
+}
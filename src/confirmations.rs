@@ -0,0 +1,266 @@
+//! Helpers for inspecting confirmations, both as returned by the confirmation queries and as submitted inside
+//! `MsgSubmitEthereumTxConfirmation`
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use gravity_proto::gravity::{
+    BatchTxConfirmation, ContractCallTxConfirmation, SignerSetTx, SignerSetTxConfirmation,
+    SignerSetTxConfirmationsResponse,
+};
+use prost_types::Any;
+
+use crate::{address::EthAddress, bail, observer::ConfirmationKind, Result};
+
+pub const BATCH_TX_CONFIRMATION_TYPE_URL: &str = "/gravity.v1.BatchTxConfirmation";
+pub const SIGNER_SET_TX_CONFIRMATION_TYPE_URL: &str = "/gravity.v1.SignerSetTxConfirmation";
+pub const CONTRACT_CALL_TX_CONFIRMATION_TYPE_URL: &str = "/gravity.v1.ContractCallTxConfirmation";
+
+/// The cumulative power `Gravity.sol` requires of a checkpoint's signers, out of a total of `2^32` since the
+/// module normalizes signer set powers to sum to `u32::MAX`. This is roughly 66%.
+pub const POWER_THRESHOLD: u64 = 2_834_678_415;
+
+/// Returns whether `confirmed_power` out of `total_power` exceeds [`POWER_THRESHOLD`] once scaled to the
+/// contract's `2^32` total, as `Gravity.sol` checks it
+pub fn meets_power_threshold(confirmed_power: u64, total_power: u64) -> bool {
+    if total_power == 0 {
+        return false;
+    }
+
+    confirmed_power as u128 * (1u128 << 32) > POWER_THRESHOLD as u128 * total_power as u128
+}
+
+/// How much of a signer set's power has validly confirmed an outgoing tx
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Readiness {
+    pub confirmed_power: u64,
+    pub total_power: u64,
+    /// Number of signer set members with a valid confirmation
+    pub confirmed_count: usize,
+    pub total_members: usize,
+    /// Members without a valid confirmation. Members whose address doesn't parse are counted in the totals but
+    /// can't be listed here.
+    pub missing: Vec<EthAddress>,
+}
+
+impl Readiness {
+    /// Tallies the members of `signer_set` found in `confirmed`, the signers of the confirmations already known
+    /// to be valid. Signers outside the set are ignored.
+    pub fn new(signer_set: &SignerSetTx, confirmed: &[EthAddress]) -> Self {
+        let mut readiness = Readiness {
+            total_members: signer_set.signers.len(),
+            ..Default::default()
+        };
+        for signer in signer_set.signers.iter() {
+            readiness.total_power = readiness.total_power.saturating_add(signer.power);
+            match signer.ethereum_address.parse::<EthAddress>() {
+                Ok(address) if confirmed.contains(&address) => {
+                    readiness.confirmed_power = readiness.confirmed_power.saturating_add(signer.power);
+                    readiness.confirmed_count += 1;
+                }
+                Ok(address) => readiness.missing.push(address),
+                Err(_) => {}
+            }
+        }
+
+        readiness
+    }
+
+    /// Returns whether the confirmed power exceeds [`POWER_THRESHOLD`], so the tx can be relayed
+    pub fn is_ready(&self) -> bool {
+        meets_power_threshold(self.confirmed_power, self.total_power)
+    }
+
+    /// The fraction of the total power that has confirmed, between 0 and 1
+    pub fn power_fraction(&self) -> f64 {
+        if self.total_power == 0 {
+            return 0.0;
+        }
+
+        self.confirmed_power as f64 / self.total_power as f64
+    }
+}
+
+/// Arrival rate of a tx's confirmations, computed from [`Readiness`] tallies recorded over time, for example by
+/// polling [`SommGravityExt::query_batch_readiness`](crate::extension::SommGravityExt::query_batch_readiness).
+/// Only the most recent `max_samples` tallies are kept, so the rate follows recent progress rather than the
+/// tx's whole history.
+#[derive(Clone, Debug)]
+pub struct ConfirmationRateTracker {
+    samples: VecDeque<(Instant, Readiness)>,
+    max_samples: usize,
+}
+
+/// When a tx is expected to reach [`POWER_THRESHOLD`], as projected by [`ConfirmationRateTracker::projection`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuorumProjection {
+    /// The latest tally already meets the threshold
+    Reached,
+    /// The threshold is expected to be met this long after the latest tally
+    In(Duration),
+    /// No power was confirmed across the recorded tallies, so no estimate can be made
+    Stalled,
+    /// Fewer than two tallies, or tallies taken at the same instant, have been recorded
+    Unknown,
+}
+
+impl ConfirmationRateTracker {
+    /// Creates a tracker keeping at most `max_samples` tallies, at least two
+    pub fn new(max_samples: usize) -> Self {
+        let max_samples = max_samples.max(2);
+
+        Self {
+            samples: VecDeque::with_capacity(max_samples),
+            max_samples,
+        }
+    }
+
+    /// Records the tally observed at `at`. Tallies are expected in chronological order.
+    pub fn record(&mut self, at: Instant, readiness: Readiness) {
+        if self.samples.len() == self.max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, readiness));
+    }
+
+    /// The latest recorded tally
+    pub fn latest(&self) -> Option<&Readiness> {
+        self.samples.back().map(|(_, readiness)| readiness)
+    }
+
+    /// New valid confirmations per minute across the recorded tallies, or `None` if the rate is unknown
+    pub fn confirmations_per_minute(&self) -> Option<f64> {
+        let ((first_at, first), (last_at, last)) = self.span()?;
+        let confirmations = last.confirmed_count as f64 - first.confirmed_count as f64;
+
+        Some(confirmations.max(0.0) * 60.0 / last_at.duration_since(*first_at).as_secs_f64())
+    }
+
+    /// Confirmed power per second across the recorded tallies, or `None` if the rate is unknown
+    pub fn power_per_second(&self) -> Option<f64> {
+        let ((first_at, first), (last_at, last)) = self.span()?;
+        let power = last.confirmed_power as f64 - first.confirmed_power as f64;
+
+        Some(power.max(0.0) / last_at.duration_since(*first_at).as_secs_f64())
+    }
+
+    /// Projects when the tx will reach [`POWER_THRESHOLD`] by extrapolating the confirmed power rate linearly
+    /// from the latest tally
+    pub fn projection(&self) -> QuorumProjection {
+        let latest = match self.latest() {
+            Some(latest) => latest,
+            None => return QuorumProjection::Unknown,
+        };
+        if latest.is_ready() {
+            return QuorumProjection::Reached;
+        }
+        let rate = match self.power_per_second() {
+            Some(rate) => rate,
+            None => return QuorumProjection::Unknown,
+        };
+        if rate <= 0.0 {
+            return QuorumProjection::Stalled;
+        }
+
+        // The smallest power strictly above the threshold once scaled to the tally's total
+        let required = ((POWER_THRESHOLD as u128 * latest.total_power as u128) >> 32) + 1;
+        let remaining = required.saturating_sub(latest.confirmed_power as u128) as f64;
+
+        QuorumProjection::In(Duration::from_secs_f64(remaining / rate))
+    }
+
+    fn span(&self) -> Option<(&(Instant, Readiness), &(Instant, Readiness))> {
+        let first = self.samples.front()?;
+        let last = self.samples.back()?;
+        if last.0 <= first.0 {
+            return None;
+        }
+
+        Some((first, last))
+    }
+}
+
+/// Returns which kind of confirmation the `confirmation` of a `MsgSubmitEthereumTxConfirmation` holds, based on
+/// the message name in its type_url. The proto package is not checked, so confirmations encoded through a
+/// [`MsgContext`](crate::msgs::MsgContext) for a package other than `gravity.v1` are recognized too. Errors on
+/// any other message name.
+pub fn classify_confirmation(confirmation: &Any) -> Result<ConfirmationKind> {
+    let name = confirmation
+        .type_url
+        .strip_prefix('/')
+        .and_then(|url| url.rsplit_once('.'))
+        .filter(|(package, _)| !package.is_empty())
+        .map(|(_, name)| name);
+
+    match name {
+        Some("BatchTxConfirmation") => Ok(ConfirmationKind::Batch),
+        Some("SignerSetTxConfirmation") => Ok(ConfirmationKind::SignerSet),
+        Some("ContractCallTxConfirmation") => Ok(ConfirmationKind::ContractCall),
+        _ => bail!("unrecognized confirmation type_url {}", confirmation.type_url),
+    }
+}
+
+/// Decodes a batch confirmation, erroring if the Any holds a different type
+pub fn decode_batch_confirmation(confirmation: &Any) -> Result<BatchTxConfirmation> {
+    decode_confirmation(confirmation, ConfirmationKind::Batch)
+}
+
+/// Decodes a signer set confirmation, erroring if the Any holds a different type
+pub fn decode_signer_set_confirmation(confirmation: &Any) -> Result<SignerSetTxConfirmation> {
+    decode_confirmation(confirmation, ConfirmationKind::SignerSet)
+}
+
+/// Decodes a contract call confirmation, erroring if the Any holds a different type
+pub fn decode_contract_call_confirmation(confirmation: &Any) -> Result<ContractCallTxConfirmation> {
+    decode_confirmation(confirmation, ConfirmationKind::ContractCall)
+}
+
+fn decode_confirmation<M: prost::Message + Default>(confirmation: &Any, expected: ConfirmationKind) -> Result<M> {
+    let kind = classify_confirmation(confirmation)?;
+    if kind != expected {
+        bail!("expected a {:?} confirmation, got {}", expected, confirmation.type_url)
+    }
+
+    match M::decode(confirmation.value.as_slice()) {
+        Ok(message) => Ok(message),
+        Err(e) => bail!("failed to decode {}: {}", confirmation.type_url, e),
+    }
+}
+
+/// Membership helpers over the signers that confirmed a signer set
+pub trait SignerSetConfirmationsExt {
+    /// Returns the parsed `ethereum_signer` of each confirmation, skipping any that are malformed
+    fn confirming_signers(&self) -> Vec<EthAddress>;
+    /// Returns the parsed signers along with the raw `ethereum_signer` strings that failed to parse
+    fn confirming_signers_checked(&self) -> (Vec<EthAddress>, Vec<String>);
+    /// Returns whether `eth_address` submitted a confirmation
+    fn contains(&self, eth_address: &EthAddress) -> bool;
+}
+
+impl SignerSetConfirmationsExt for SignerSetTxConfirmationsResponse {
+    fn confirming_signers(&self) -> Vec<EthAddress> {
+        self.confirming_signers_checked().0
+    }
+
+    fn confirming_signers_checked(&self) -> (Vec<EthAddress>, Vec<String>) {
+        let mut signers = Vec::new();
+        let mut malformed = Vec::new();
+        for confirmation in self.signatures.iter() {
+            match confirmation.ethereum_signer.parse::<EthAddress>() {
+                Ok(signer) => signers.push(signer),
+                Err(_) => malformed.push(confirmation.ethereum_signer.clone()),
+            }
+        }
+
+        (signers, malformed)
+    }
+
+    fn contains(&self, eth_address: &EthAddress) -> bool {
+        self.signatures.iter().any(|c| {
+            c.ethereum_signer
+                .parse::<EthAddress>()
+                .map_or(false, |signer| &signer == eth_address)
+        })
+    }
+}
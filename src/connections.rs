@@ -0,0 +1,40 @@
+//! An optional process wide cap on open gravity gRPC connections.
+//!
+//! Every query made through a [`GrpcClient`](ocular::grpc::GrpcClient) dials a new connection, so a process
+//! running many queries at once, such as a server answering requests in parallel, can exhaust its file
+//! descriptors. After [`set_global_connection_limit`], opening a connection past the limit waits for an open
+//! one to close instead of failing. Connections made by
+//! [`SommGravityQueryClient`](crate::extension::SommGravityQueryClient)'s `new_client`,
+//! [`BankQueryClient::connect`](crate::extension::BankQueryClient::connect) and
+//! [`GravitySession::connect`](crate::session::GravitySession::connect) count toward the limit until the
+//! client or the last clone of the session is dropped. Clients built with `from_channel` reuse an existing
+//! connection and don't count.
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{bail, Result};
+
+static CONNECTION_LIMIT: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+/// Caps the number of gravity gRPC connections open at once across the process. The limit can only be set
+/// once, and should be set before any connection is opened since connections opened earlier aren't counted.
+pub fn set_global_connection_limit(limit: usize) -> Result<()> {
+    if limit == 0 {
+        bail!("connection limit must be non-zero")
+    }
+    if CONNECTION_LIMIT.set(Arc::new(Semaphore::new(limit))).is_err() {
+        bail!("the global connection limit has already been set")
+    }
+
+    Ok(())
+}
+
+/// Waits for room under the global limit, returning the permit to hold while the connection is open, or `None`
+/// if no limit is set
+pub(crate) async fn acquire_connection() -> Option<OwnedSemaphorePermit> {
+    let semaphore = CONNECTION_LIMIT.get()?.clone();
+
+    // The semaphore is never closed, so acquiring can't fail
+    semaphore.acquire_owned().await.ok()
+}
@@ -0,0 +1,406 @@
+//! Human readable formatting for gravity messages, governed by a crate-wide [`RedactionPolicy`]
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use gravity_proto::gravity as proto;
+use ocular::cosmrs::Coin;
+use prost_types::Any;
+
+use crate::{bail, msgs::SommGravity, Result};
+
+static REDACTION_POLICY: AtomicU8 = AtomicU8::new(RedactionPolicy::Redacted as u8);
+
+/// Controls how signatures and addresses are rendered by the [`fmt::Debug`] and [`fmt::Display`]
+/// implementations in this crate. Defaults to [`RedactionPolicy::Redacted`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RedactionPolicy {
+    /// Render signatures as full hex and addresses verbatim
+    Full = 0,
+    /// Render only the length of signatures and the first and last characters of addresses
+    #[default]
+    Redacted = 1,
+}
+
+/// Sets the redaction policy used by all formatting in this crate
+pub fn set_redaction_policy(policy: RedactionPolicy) {
+    REDACTION_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// Returns the redaction policy currently in effect
+pub fn redaction_policy() -> RedactionPolicy {
+    match REDACTION_POLICY.load(Ordering::Relaxed) {
+        0 => RedactionPolicy::Full,
+        _ => RedactionPolicy::Redacted,
+    }
+}
+
+/// Formats signature bytes according to the current [`RedactionPolicy`]
+pub fn format_signature(signature: &[u8]) -> String {
+    match redaction_policy() {
+        RedactionPolicy::Full => format!("0x{}", hex::encode(signature)),
+        RedactionPolicy::Redacted => format!("<{} bytes redacted>", signature.len()),
+    }
+}
+
+/// Formats a cosmos or ethereum address according to the current [`RedactionPolicy`]
+pub fn format_address(address: &str) -> String {
+    match redaction_policy() {
+        RedactionPolicy::Full => address.to_string(),
+        RedactionPolicy::Redacted => {
+            // addresses are ascii, but fall back to the full string rather than splitting a char
+            if address.len() <= 12 || !address.is_ascii() {
+                return address.to_string();
+            }
+            format!("{}...{}", &address[..8], &address[address.len() - 4..])
+        }
+    }
+}
+
+fn format_any(any: &Any) -> String {
+    format!("{} ({} bytes)", any.type_url, any.value.len())
+}
+
+impl fmt::Debug for SommGravity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SommGravity::SendToEthereum {
+                sender,
+                ethereum_recipient,
+                amount,
+                bridge_fee,
+            } => f
+                .debug_struct("SendToEthereum")
+                .field("sender", &format_address(sender))
+                .field("ethereum_recipient", &format_address(ethereum_recipient))
+                .field("amount", amount)
+                .field("bridge_fee", bridge_fee)
+                .finish(),
+            SommGravity::CancelSendToEthereum { sender, id } => f
+                .debug_struct("CancelSendToEthereum")
+                .field("sender", &format_address(sender))
+                .field("id", id)
+                .finish(),
+            SommGravity::RequestBatchTx { denom, signer } => f
+                .debug_struct("RequestBatchTx")
+                .field("denom", denom)
+                .field("signer", &format_address(signer))
+                .finish(),
+            SommGravity::SubmitEthereumTxConfirmation {
+                confirmation,
+                signer,
+            } => f
+                .debug_struct("SubmitEthereumTxConfirmation")
+                .field("confirmation", &format_any(confirmation))
+                .field("signer", &format_address(signer))
+                .finish(),
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope,
+                invalidation_nonce,
+                ethereum_signer,
+                signature,
+            } => f
+                .debug_struct("ContractCallTxConfirmation")
+                .field("invalidation_scope", &hex::encode(invalidation_scope))
+                .field("invalidation_nonce", invalidation_nonce)
+                .field("ethereum_signer", &format_address(ethereum_signer))
+                .field("signature", &format_signature(signature))
+                .finish(),
+            SommGravity::BatchTxConfirmation {
+                token_contract_address,
+                batch_nonce,
+                ethereum_signer,
+                signature,
+            } => f
+                .debug_struct("BatchTxConfirmation")
+                .field("token_contract_address", token_contract_address)
+                .field("batch_nonce", batch_nonce)
+                .field("ethereum_signer", &format_address(ethereum_signer))
+                .field("signature", &format_signature(signature))
+                .finish(),
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce,
+                ethereum_signer,
+                signature,
+            } => f
+                .debug_struct("SignerSetTxConfirmation")
+                .field("signer_set_nonce", signer_set_nonce)
+                .field("ethereum_signer", &format_address(ethereum_signer))
+                .field("signature", &format_signature(signature))
+                .finish(),
+            SommGravity::SubmitEthereumEvent { event, signer } => f
+                .debug_struct("SubmitEthereumEvent")
+                .field("event", &format_any(event))
+                .field("signer", &format_address(signer))
+                .finish(),
+            SommGravity::SetDelegateKeys {
+                validator_address,
+                orchestrator_address,
+                ethereum_address,
+                eth_signature,
+            } => f
+                .debug_struct("SetDelegateKeys")
+                .field("validator_address", &format_address(validator_address))
+                .field("orchestrator_address", &format_address(orchestrator_address))
+                .field("ethereum_address", &format_address(ethereum_address))
+                .field("eth_signature", &format_signature(eth_signature))
+                .finish(),
+            SommGravity::DelegateKeysSignMsg {
+                validator_address,
+                nonce,
+            } => f
+                .debug_struct("DelegateKeysSignMsg")
+                .field("validator_address", &format_address(validator_address))
+                .field("nonce", nonce)
+                .finish(),
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height,
+                signer,
+            } => f
+                .debug_struct("SubmitEthereumHeightVote")
+                .field("ethereum_height", ethereum_height)
+                .field("signer", &format_address(signer))
+                .finish(),
+        }
+    }
+}
+
+impl fmt::Display for SommGravity<'_> {
+    /// Renders a one line summary of the message
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SommGravity::SendToEthereum {
+                sender,
+                ethereum_recipient,
+                amount,
+                bridge_fee,
+            } => write!(
+                f,
+                "send {}{} (fee {}{}) from {} to {}",
+                amount.amount,
+                amount.denom,
+                bridge_fee.amount,
+                bridge_fee.denom,
+                format_address(sender),
+                format_address(ethereum_recipient)
+            ),
+            SommGravity::CancelSendToEthereum { sender, id } => {
+                write!(f, "cancel send {} from {}", id, format_address(sender))
+            }
+            SommGravity::RequestBatchTx { denom, signer } => {
+                write!(f, "request batch of {} by {}", denom, format_address(signer))
+            }
+            SommGravity::SubmitEthereumTxConfirmation {
+                confirmation,
+                signer,
+            } => write!(
+                f,
+                "submit confirmation {} by {}",
+                format_any(confirmation),
+                format_address(signer)
+            ),
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope,
+                invalidation_nonce,
+                ethereum_signer,
+                signature,
+            } => write!(
+                f,
+                "contract call confirmation for scope 0x{} nonce {} by {} signature {}",
+                hex::encode(invalidation_scope),
+                invalidation_nonce,
+                format_address(ethereum_signer),
+                format_signature(signature)
+            ),
+            SommGravity::BatchTxConfirmation {
+                token_contract_address,
+                batch_nonce,
+                ethereum_signer,
+                signature,
+            } => write!(
+                f,
+                "batch confirmation for {} nonce {} by {} signature {}",
+                token_contract_address,
+                batch_nonce,
+                format_address(ethereum_signer),
+                format_signature(signature)
+            ),
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce,
+                ethereum_signer,
+                signature,
+            } => write!(
+                f,
+                "signer set confirmation for nonce {} by {} signature {}",
+                signer_set_nonce,
+                format_address(ethereum_signer),
+                format_signature(signature)
+            ),
+            SommGravity::SubmitEthereumEvent { event, signer } => write!(
+                f,
+                "submit ethereum event {} by {}",
+                format_any(event),
+                format_address(signer)
+            ),
+            SommGravity::SetDelegateKeys {
+                validator_address,
+                orchestrator_address,
+                ethereum_address,
+                eth_signature,
+            } => write!(
+                f,
+                "set delegate keys for {}: orchestrator {}, ethereum {} signature {}",
+                format_address(validator_address),
+                format_address(orchestrator_address),
+                format_address(ethereum_address),
+                format_signature(eth_signature)
+            ),
+            SommGravity::DelegateKeysSignMsg {
+                validator_address,
+                nonce,
+            } => write!(
+                f,
+                "delegate keys sign msg for {} nonce {}",
+                format_address(validator_address),
+                nonce
+            ),
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height,
+                signer,
+            } => write!(
+                f,
+                "ethereum height vote {} by {}",
+                ethereum_height,
+                format_address(signer)
+            ),
+        }
+    }
+}
+
+/// Renders a one line summary of each gravity message among a tx's msgs, using [`SommGravity`]'s `Display`.
+/// Confirmations submitted in a `MsgSubmitEthereumTxConfirmation` are described by their contents. Messages
+/// outside the `gravity` proto package are skipped, while gravity messages this crate doesn't know or can't
+/// decode get a line noting their type_url.
+pub fn describe_gravity_tx(msgs: &[Any]) -> Vec<String> {
+    msgs.iter().filter_map(describe_gravity_msg).collect()
+}
+
+fn describe_gravity_msg(any: &Any) -> Option<String> {
+    let name = any.type_url.strip_prefix("/gravity.")?;
+    let name = name.rsplit('.').next().unwrap_or(name);
+    let line = match name {
+        "MsgSendToEthereum" => decode::<proto::MsgSendToEthereum>(any).and_then(|m| {
+            Ok(SommGravity::SendToEthereum {
+                sender: &m.sender,
+                ethereum_recipient: &m.ethereum_recipient,
+                amount: decode_coin("amount", m.amount.clone())?,
+                bridge_fee: decode_coin("bridge_fee", m.bridge_fee.clone())?,
+            }
+            .to_string())
+        }),
+        "MsgCancelSendToEthereum" => decode::<proto::MsgCancelSendToEthereum>(any).map(|m| {
+            SommGravity::CancelSendToEthereum {
+                sender: &m.sender,
+                id: m.id,
+            }
+            .to_string()
+        }),
+        "MsgRequestBatchTx" => decode::<proto::MsgRequestBatchTx>(any).map(|m| {
+            SommGravity::RequestBatchTx {
+                denom: &m.denom,
+                signer: &m.signer,
+            }
+            .to_string()
+        }),
+        "MsgSubmitEthereumTxConfirmation" => {
+            decode::<proto::MsgSubmitEthereumTxConfirmation>(any).map(|m| match m.confirmation.as_ref() {
+                Some(confirmation) => format!(
+                    "submit {} by {}",
+                    describe_gravity_msg(confirmation).unwrap_or_else(|| format_any(confirmation)),
+                    format_address(&m.signer)
+                ),
+                None => format!("submit empty confirmation by {}", format_address(&m.signer)),
+            })
+        }
+        "ContractCallTxConfirmation" => decode::<proto::ContractCallTxConfirmation>(any).map(|m| {
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope: m.invalidation_scope,
+                invalidation_nonce: m.invalidation_nonce,
+                ethereum_signer: &m.ethereum_signer,
+                signature: m.signature,
+            }
+            .to_string()
+        }),
+        "BatchTxConfirmation" => decode::<proto::BatchTxConfirmation>(any).map(|m| {
+            SommGravity::BatchTxConfirmation {
+                token_contract_address: &m.token_contract,
+                batch_nonce: m.batch_nonce,
+                ethereum_signer: &m.ethereum_signer,
+                signature: m.signature,
+            }
+            .to_string()
+        }),
+        "SignerSetTxConfirmation" => decode::<proto::SignerSetTxConfirmation>(any).map(|m| {
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce: m.signer_set_nonce,
+                ethereum_signer: &m.ethereum_signer,
+                signature: m.signature,
+            }
+            .to_string()
+        }),
+        "MsgSubmitEthereumEvent" => decode::<proto::MsgSubmitEthereumEvent>(any).map(|m| {
+            SommGravity::SubmitEthereumEvent {
+                event: m.event.unwrap_or_default(),
+                signer: &m.signer,
+            }
+            .to_string()
+        }),
+        "MsgDelegateKeys" => decode::<proto::MsgDelegateKeys>(any).map(|m| {
+            SommGravity::SetDelegateKeys {
+                validator_address: &m.validator_address,
+                orchestrator_address: &m.orchestrator_address,
+                ethereum_address: &m.ethereum_address,
+                eth_signature: m.eth_signature,
+            }
+            .to_string()
+        }),
+        "DelegateKeysSignMsg" => decode::<proto::DelegateKeysSignMsg>(any).map(|m| {
+            SommGravity::DelegateKeysSignMsg {
+                validator_address: &m.validator_address,
+                nonce: m.nonce,
+            }
+            .to_string()
+        }),
+        "MsgEthereumHeightVote" => decode::<proto::MsgEthereumHeightVote>(any).map(|m| {
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height: m.ethereum_height,
+                signer: &m.signer,
+            }
+            .to_string()
+        }),
+        _ => Ok(format!("unknown gravity message {}", any.type_url)),
+    };
+
+    Some(line.unwrap_or_else(|e| format!("undecodable {}: {}", any.type_url, e)))
+}
+
+fn decode<M: prost::Message + Default>(any: &Any) -> Result<M> {
+    match M::decode(any.value.as_slice()) {
+        Ok(message) => Ok(message),
+        Err(e) => bail!("{}", e),
+    }
+}
+
+fn decode_coin<C>(field: &str, coin: Option<C>) -> Result<Coin>
+where
+    Coin: TryFrom<C>,
+    <Coin as TryFrom<C>>::Error: fmt::Display,
+{
+    match coin.map(Coin::try_from) {
+        Some(Ok(coin)) => Ok(coin),
+        Some(Err(e)) => bail!("invalid {}: {}", field, e),
+        None => bail!("missing {}", field),
+    }
+}
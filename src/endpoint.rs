@@ -0,0 +1,122 @@
+//! Helpers for building and checking gRPC endpoint URLs.
+//!
+//! Endpoints are parsed as URIs by tonic, which already accepts bracketed IPv6 literals such as
+//! `http://[::1]:9090`. A bare IPv6 address can't be joined with a port by string formatting alone, so build
+//! URLs from a host and port with [`endpoint_url`] rather than `format!`.
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+use tonic::transport::Uri;
+
+use crate::{bail, Result};
+
+/// Joins `scheme`, `host` and `port` into an endpoint URL, bracketing `host` if it is an IPv6 literal.
+/// Hosts that are already bracketed are left as is.
+pub fn endpoint_url(scheme: &str, host: &str, port: u16) -> String {
+    if host.parse::<Ipv6Addr>().is_ok() {
+        format!("{}://[{}]:{}", scheme, host, port)
+    } else {
+        format!("{}://{}:{}", scheme, host, port)
+    }
+}
+
+/// Returns the endpoint URL of a socket address, bracketing IPv6 addresses
+pub fn endpoint_url_from_addr(scheme: &str, addr: SocketAddr) -> String {
+    match addr.ip() {
+        IpAddr::V4(ip) => format!("{}://{}:{}", scheme, ip, addr.port()),
+        IpAddr::V6(ip) => format!("{}://[{}]:{}", scheme, ip, addr.port()),
+    }
+}
+
+/// Checks that `endpoint` is an absolute `http` or `https` URL with a host, as the gRPC transport requires.
+/// An unbracketed IPv6 literal with a port, such as `http://::1:9090`, is rejected with a hint, since it can't
+/// be told apart from an address without a port.
+pub fn validate_endpoint(endpoint: &str) -> Result<()> {
+    let uri = match endpoint.parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(e) => {
+            if endpoint.matches(':').count() > 2 && !endpoint.contains('[') {
+                bail!(
+                    "invalid endpoint {}: {}. IPv6 addresses must be bracketed, as in http://[::1]:9090",
+                    endpoint,
+                    e
+                )
+            }
+            bail!("invalid endpoint {}: {}", endpoint, e)
+        }
+    };
+    match uri.scheme_str() {
+        Some("http") | Some("https") => {}
+        Some(other) => bail!("invalid endpoint {}: unsupported scheme {}", endpoint, other),
+        None => bail!("invalid endpoint {}: missing http:// or https:// scheme", endpoint),
+    }
+    if uri.host().map_or(true, str::is_empty) {
+        bail!("invalid endpoint {}: missing host", endpoint)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::TcpListener, thread};
+
+    use super::*;
+    use crate::session::GravitySession;
+
+    #[test]
+    fn endpoint_url_brackets_ipv6() {
+        assert_eq!(endpoint_url("http", "::1", 9090), "http://[::1]:9090");
+        assert_eq!(endpoint_url("http", "[::1]", 9090), "http://[::1]:9090");
+        assert_eq!(endpoint_url("https", "fe80::1", 443), "https://[fe80::1]:443");
+        assert_eq!(endpoint_url("http", "127.0.0.1", 9090), "http://127.0.0.1:9090");
+        assert_eq!(endpoint_url("https", "grpc.example.com", 443), "https://grpc.example.com:443");
+    }
+
+    #[test]
+    fn endpoint_url_from_addr_brackets_ipv6() {
+        let v6 = "[::1]:9090".parse::<SocketAddr>().unwrap();
+        let v4 = "127.0.0.1:9090".parse::<SocketAddr>().unwrap();
+        assert_eq!(endpoint_url_from_addr("http", v6), "http://[::1]:9090");
+        assert_eq!(endpoint_url_from_addr("http", v4), "http://127.0.0.1:9090");
+    }
+
+    #[test]
+    fn validate_endpoint_accepts_bracketed_ipv6() {
+        validate_endpoint("http://[::1]:9090").unwrap();
+        validate_endpoint("https://[2001:db8::1]:443").unwrap();
+        validate_endpoint("http://127.0.0.1:9090").unwrap();
+        validate_endpoint("https://grpc.example.com").unwrap();
+    }
+
+    #[test]
+    fn validate_endpoint_rejects_unbracketed_ipv6() {
+        let err = validate_endpoint("http://::1:9090").unwrap_err();
+        assert!(err.to_string().contains("must be bracketed"), "{}", err);
+    }
+
+    #[test]
+    fn validate_endpoint_rejects_bad_scheme_and_host() {
+        assert!(validate_endpoint("grpc.example.com:9090").is_err());
+        assert!(validate_endpoint("ftp://grpc.example.com:9090").is_err());
+        assert!(validate_endpoint("http://").is_err());
+    }
+
+    #[tokio::test]
+    async fn connects_to_ipv6_listener() {
+        // Skip on hosts without IPv6 loopback
+        let listener = match TcpListener::bind("[::1]:0") {
+            Ok(listener) => listener,
+            Err(_) => return,
+        };
+        let endpoint = endpoint_url_from_addr("http", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                while matches!(stream.read(&mut buf), Ok(n) if n > 0) {}
+            }
+        });
+
+        let session = GravitySession::connect(&endpoint).await.unwrap();
+        assert_eq!(session.endpoint(), endpoint);
+    }
+}
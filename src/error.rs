@@ -0,0 +1,93 @@
+//! Typed errors surfaced by the gravity queries
+use std::fmt;
+
+use tonic::{Code, Status};
+
+use crate::Error;
+
+/// Returned when a node's responses indicate it is not serving a compatible gravity module, either because
+/// the query service is missing entirely or because a response failed to decode against this crate's protos.
+/// This usually means the endpoint is not a Sommelier node or runs a different gravity module version.
+#[derive(Clone, Debug)]
+pub struct IncompatibleNode {
+    /// The query method that failed
+    pub method: &'static str,
+    /// The server's error message
+    pub reason: String,
+}
+
+impl fmt::Display for IncompatibleNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node is incompatible with the gravity query {}: {}",
+            self.method, self.reason
+        )
+    }
+}
+
+impl std::error::Error for IncompatibleNode {}
+
+/// Maps a failed query's status to [`IncompatibleNode`] when it looks like a proto mismatch, otherwise passes
+/// the status through unchanged
+pub(crate) fn map_status(method: &'static str, status: Status) -> Error {
+    let incompatible = match status.code() {
+        Code::Unimplemented => true,
+        Code::Internal => status.message().contains("failed to decode"),
+        _ => false,
+    };
+
+    if incompatible {
+        Error::new(IncompatibleNode {
+            method,
+            reason: status.message().to_string(),
+        })
+    } else {
+        Error::new(status)
+    }
+}
+
+/// Returned by [`SommGravitySubmit::submit`](crate::submit::SommGravitySubmit::submit) when a broadcast tx is
+/// rejected, either by the mempool's `CheckTx` or on execution in a block
+#[derive(Clone, Debug)]
+pub struct TxFailed {
+    /// Hash of the rejected tx
+    pub tx_hash: String,
+    /// `check_tx` or `deliver_tx`
+    pub phase: &'static str,
+    pub code: u32,
+    /// The node's log for the failure, which usually names the failing message and why
+    pub log: String,
+}
+
+impl fmt::Display for TxFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tx {} failed in {} with code {}: {}",
+            self.tx_hash, self.phase, self.code, self.log
+        )
+    }
+}
+
+impl std::error::Error for TxFailed {}
+
+/// Returned by [`SommGravityExt::assert_monotonic_signer_sets`](crate::extension::SommGravityExt::assert_monotonic_signer_sets)
+/// when signer sets are out of order
+#[derive(Clone, Debug)]
+pub struct NonMonotonicSignerSets {
+    /// Nonces of the signer sets whose nonce or height is not greater than the previous set's
+    pub nonces: Vec<u64>,
+}
+
+impl fmt::Display for NonMonotonicSignerSets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "signer set nonces or heights do not strictly increase at nonces {:?}",
+            self.nonces
+        )
+    }
+}
+
+impl std::error::Error for NonMonotonicSignerSets {}
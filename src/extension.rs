@@ -1,35 +1,186 @@
-//! Defines an extension trait for Sommelier's gravity module queries and messages
+//! Defines an extension trait for Sommelier's gravity module queries
+#[cfg(feature = "gzip")]
+use std::io::Write;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    future::Future,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
 use async_trait::async_trait;
-use eyre::{bail, Report, Result};
+#[cfg(feature = "serde")]
+use futures::io::AsyncWrite;
+use futures::{join, stream, stream::LocalBoxStream, try_join, StreamExt};
 use gravity_proto::gravity::*;
+use k256::ecdsa::SigningKey;
 use ocular::{
-    grpc::{GrpcClient, PageRequest, ConstructClient}, cosmrs::Coin, tx::{UnsignedTx, ModuleMsg},
+    grpc::{GrpcClient, PageRequest, ConstructClient}, cosmrs::{proto::cosmos::bank::v1beta1::QueryBalanceRequest, Coin}, tx::{ModuleMsg, UnsignedTx},
+};
+use prost_types::Timestamp;
+use tokio::sync::OwnedSemaphorePermit;
+
+use crate::{
+    address::{self, EthAddress, GRAVITY_MODULE_NAME, SOMMELIER_ACCOUNT_PREFIX},
+    amounts::{batch_total_fees, format_amount, parse_erc20_amount, total_amount_plus_fee_in},
+    bail,
+    cache::{BRIDGE_CONTRACT, ERC20_DECIMALS, ERC20_TO_DENOM, GRAVITY_ID},
+    checkpoint::parse_gravity_id,
+    clock::{Clock, SystemClock},
+    confirmations::Readiness,
+    connections::acquire_connection,
+    endpoint::validate_endpoint,
+    error::{map_status, NonMonotonicSignerSets},
+    invalidation::Invalidation,
+    msgs::SommGravity,
+    pagination::PageLimits,
+    signature::{
+        sign_batch_confirmation, sign_contract_call_confirmation, sign_signer_set_confirmation,
+        verify_batch_confirmation, verify_contract_call_confirmation, verify_signer_set_confirmation,
+    },
+    wire_log, Result,
 };
-use prost_types::Any;
+#[cfg(feature = "serde")]
+use crate::snapshot::{self, ExportOptions, GravityStateSnapshot};
 
 pub type SommGravityParams = gravity_proto::gravity::Params;
 
+/// How many batches' confirmations [`SommGravityExt::relayable_batches_after`] fetches at once
+const RELAYABLE_SCAN_CONCURRENCY: usize = 8;
+
+/// The bank module's query client, used to read the gravity module account's balances
+pub struct BankQueryClient {
+    inner: ocular::cosmrs::proto::cosmos::bank::v1beta1::query_client::QueryClient<tonic::transport::Channel>,
+    /// Held while this client's own connection is open, if a global connection limit is set
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl BankQueryClient {
+    /// Wraps an already established channel, allowing it to be shared with other module clients
+    pub fn from_channel(channel: tonic::transport::Channel) -> Self {
+        Self {
+            inner: ocular::cosmrs::proto::cosmos::bank::v1beta1::query_client::QueryClient::new(channel),
+            _permit: None,
+        }
+    }
+
+    /// Dials `endpoint`, first waiting for room under the global connection limit if one is set
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        let permit = acquire_connection().await;
+        match ocular::cosmrs::proto::cosmos::bank::v1beta1::query_client::QueryClient::connect(endpoint.to_string())
+            .await
+        {
+            Ok(inner) => Ok(Self { inner, _permit: permit }),
+            Err(e) => bail!("failed to connect to {}: {}", endpoint, e),
+        }
+    }
+}
+
 /// The (Sommelier) gravity module's query client proto definition wrapper
 pub struct SommGravityQueryClient {
     inner: gravity_proto::gravity::query_client::QueryClient<tonic::transport::Channel>,
+    /// Held while this client's own connection is open, if a global connection limit is set
+    _permit: Option<OwnedSemaphorePermit>,
 }
 
 
+impl SommGravityQueryClient {
+    /// Wraps an already established channel, allowing it to be shared with other module clients
+    pub fn from_channel(channel: tonic::transport::Channel) -> Self {
+        Self {
+            inner: gravity_proto::gravity::query_client::QueryClient::new(channel),
+            _permit: None,
+        }
+    }
+
+    /// Connects to `endpoint` and issues a `params` query before returning, so an endpoint that is unreachable
+    /// or isn't serving the gravity module fails here rather than on the first real query. Use
+    /// [`ConstructClient::new_client`] to skip the check.
+    pub async fn connect_validated(endpoint: &str) -> Result<Self> {
+        validate_endpoint(endpoint)?;
+        let mut client = match Self::new_client(endpoint.to_string()).await {
+            Ok(client) => client,
+            Err(e) => bail!("failed to connect to {}: {:?}", endpoint, e),
+        };
+        if let Err(e) = client.inner.params(ParamsRequest {}).await {
+            bail!(
+                "{} is not a working gravity node: {}",
+                endpoint,
+                map_status("params", e)
+            )
+        }
+
+        Ok(client)
+    }
+}
+
 #[async_trait]
 impl ConstructClient<SommGravityQueryClient> for SommGravityQueryClient {
-    async fn new_client(endpoint: String) -> Result<Self> {
+    /// Dials `endpoint`, first waiting for room under the global connection limit if one is set
+    async fn new_client(endpoint: String) -> eyre::Result<Self> {
+        let permit = acquire_connection().await;
+
         Ok(Self {
-            inner: gravity_proto::gravity::query_client::QueryClient::<tonic::transport::Channel>::connect(endpoint.to_owned()).await?
+            inner: gravity_proto::gravity::query_client::QueryClient::<tonic::transport::Channel>::connect(endpoint.to_owned()).await?,
+            _permit: permit,
         })
     }
 }
 
+/// Supplies the query client used by every [`SommGravityExt`] method. [`GrpcClient`] opens a new connection
+/// per query, while [`crate::session::GravitySession`] reuses a single channel.
+#[async_trait(?Send)]
+pub trait GravityConnection {
+    async fn gravity_client(&self) -> Result<SommGravityQueryClient>;
+    /// The endpoint queries are sent to, used to key cached results
+    fn gravity_endpoint(&self) -> String;
+    /// The bounds applied to the `query_all_*` aggregators made through this connection
+    fn page_limits(&self) -> PageLimits {
+        PageLimits::default()
+    }
+    /// Returns a bank query client for the same node. By default this dials [`GravityConnection::gravity_endpoint`]
+    /// anew, counting toward the global connection limit.
+    async fn bank_client(&self) -> Result<BankQueryClient> {
+        BankQueryClient::connect(&self.gravity_endpoint()).await
+    }
+}
+
+#[async_trait(?Send)]
+impl GravityConnection for GrpcClient {
+    async fn gravity_client(&self) -> Result<SommGravityQueryClient> {
+        match SommGravityQueryClient::new_client(self.grpc_endpoint()).await {
+            Ok(client) => Ok(client),
+            Err(e) => bail!("failed to connect to {}: {:?}", self.grpc_endpoint(), e),
+        }
+    }
+
+    fn gravity_endpoint(&self) -> String {
+        self.grpc_endpoint()
+    }
+}
+
+/// Gravity module queries and the helpers built on them, implemented for every [`GravityConnection`].
+///
+/// Address, denom and contract parameters accept anything implementing `AsRef<str>`, so `&str`, `String`
+/// and address newtypes can be passed directly. Those methods are generic and so carry a `Self: Sized` bound,
+/// which keeps the trait object safe: `dyn SommGravityExt` can call every method without such a parameter,
+/// and code that needs the rest should be generic over `T: SommGravityExt`.
 #[async_trait(?Send)]
 pub trait SommGravityExt {
     async fn query_somm_gravity_params(&self) -> Result<ParamsResponse>;
+    /// Returns whether the bridge is currently active, as determined solely by the `bridge_active` field of
+    /// the module [`Params`]. The module does not expose a reason for a halt, so a `false` here only means
+    /// governance has set `bridge_active` to false. Tooling should refuse to build sends when this is false.
+    async fn is_bridge_active(&self) -> Result<bool>;
+    /// Signer set and batch nonces start at 1, so a nonce of 0 is rejected locally by this and the other signer
+    /// set and batch queries.
     async fn query_signer_set_tx(&self, nonce: u64) -> Result<SignerSetTxResponse>;
     async fn query_latest_signer_set_tx(&self) -> Result<SignerSetTxResponse>;
-    async fn query_batch_tx(&self, token_contract_address: &str, nonce: u64) -> Result<BatchTxResponse>;
+    /// Fetches the latest signer set and its confirmations. If the latest nonce changes while the confirmations
+    /// are being fetched, the new signer set and its confirmations are fetched once more.
+    async fn query_latest_signer_set_confirmations(&self) -> Result<SignerSetConfirmations>;
+    async fn query_batch_tx(&self, token_contract_address: impl AsRef<str>, nonce: u64) -> Result<BatchTxResponse>
+    where
+        Self: Sized;
     async fn query_contract_call_tx(&self, invalidation_scope: Vec<u8>, invalidation_nonce: u64) -> Result<ContractCallTxResponse>;
     async fn query_signer_set_txs(&self, pagination: Option<PageRequest>)
         -> Result<SignerSetTxsResponse>;
@@ -45,149 +196,597 @@ pub trait SommGravityExt {
     async fn query_batch_tx_confirmations(
         &self,
         nonce: u64,
-        token_contract_address: &str,
-    ) -> Result<BatchTxConfirmationsResponse>;
+        token_contract_address: impl AsRef<str>,
+    ) -> Result<BatchTxConfirmationsResponse>
+    where
+        Self: Sized;
     async fn query_contract_call_tx_confirmations(
         &self,
         invalidation_scope: Vec<u8>,
         invalidation_nonce: u64,
     ) -> Result<ContractCallTxConfirmationsResponse>;
-    async fn query_unsigned_signer_set_txs(&self, address: &str) -> Result<UnsignedSignerSetTxsResponse>;
-    async fn query_unsigned_batch_txs(&self, address: &str) -> Result<UnsignedBatchTxsResponse>;
-    async fn query_unsigned_contract_call_txs(&self, address: &str) -> Result<UnsignedContractCallTxsResponse>;
+    async fn query_unsigned_signer_set_txs(&self, address: impl AsRef<str>) -> Result<UnsignedSignerSetTxsResponse>
+    where
+        Self: Sized;
+    async fn query_unsigned_batch_txs(&self, address: impl AsRef<str>) -> Result<UnsignedBatchTxsResponse>
+    where
+        Self: Sized;
+    async fn query_unsigned_contract_call_txs(&self, address: impl AsRef<str>) -> Result<UnsignedContractCallTxsResponse>
+    where
+        Self: Sized;
     async fn query_last_submitted_ethereum_event(
         &self,
-        address: &str,
-    ) -> Result<LastSubmittedEthereumEventResponse>;
-    async fn query_erc20_to_denom(&self, erc20: &str) -> Result<String>;
-    async fn query_denom_to_erc20_params(&self, denom: &str) -> Result<DenomToErc20ParamsResponse>;
-    async fn query_denom_to_erc20(&self, denom: &str) -> Result<String>;
+        address: impl AsRef<str>,
+    ) -> Result<LastSubmittedEthereumEventResponse>
+    where
+        Self: Sized;
+    async fn query_erc20_to_denom(&self, erc20: impl AsRef<str>) -> Result<String>
+    where
+        Self: Sized;
+    async fn query_denom_to_erc20_params(&self, denom: impl AsRef<str>) -> Result<DenomToErc20ParamsResponse>
+    where
+        Self: Sized;
+    async fn query_denom_to_erc20(&self, denom: impl AsRef<str>) -> Result<String>
+    where
+        Self: Sized;
     async fn query_delegate_keys_by_validator(
         &self,
-        validator_address: &str,
-    ) -> Result<DelegateKeysByValidatorResponse>;
+        validator_address: impl AsRef<str>,
+    ) -> Result<DelegateKeysByValidatorResponse>
+    where
+        Self: Sized;
     async fn query_delegate_keys_by_ethereum_signer(
         &self,
-        ethereum_signer_address: &str,
-    ) -> Result<DelegateKeysByEthereumSignerResponse>;
+        ethereum_signer_address: impl AsRef<str>,
+    ) -> Result<DelegateKeysByEthereumSignerResponse>
+    where
+        Self: Sized;
     async fn query_delegate_keys_by_orchestrator(
         &self,
-        orchestrator_address: &str,
-    ) -> Result<DelegateKeysByOrchestratorResponse>;
+        orchestrator_address: impl AsRef<str>,
+    ) -> Result<DelegateKeysByOrchestratorResponse>
+    where
+        Self: Sized;
     async fn query_delegate_keys(&self) -> Result<DelegateKeysResponse>;
     async fn query_batched_send_to_ethereums(
         &self,
-        sender_address: &str,
-    ) -> Result<BatchedSendToEthereumsResponse>;
+        sender_address: impl AsRef<str>,
+    ) -> Result<BatchedSendToEthereumsResponse>
+    where
+        Self: Sized;
     async fn query_unbatched_send_to_ethereums(
         &self,
-        sender_address: &str,
+        sender_address: impl AsRef<str>,
         paginationi: Option<PageRequest>,
-    ) -> Result<UnbatchedSendToEthereumsResponse>;
+    ) -> Result<UnbatchedSendToEthereumsResponse>
+    where
+        Self: Sized;
+    /// Pages through [`SommGravityExt::query_batch_txs`] until the node reports no further pages,
+    /// failing if the connection's [`PageLimits`] are exceeded
+    async fn query_all_batch_txs(&self) -> Result<Vec<BatchTx>>;
+    /// Pages through [`SommGravityExt::query_unbatched_send_to_ethereums`] until the node reports no further pages,
+    /// failing if the connection's [`PageLimits`] are exceeded
+    async fn query_all_unbatched_send_to_ethereums(&self, sender_address: impl AsRef<str>) -> Result<Vec<SendToEthereum>>
+    where
+        Self: Sized;
+    /// Reconciles the cosmos side accounting for an ERC20 token. The denom comes from `query_erc20_to_denom`,
+    /// the batched totals from every outgoing batch for the contract in `query_batch_txs`, the unbatched totals
+    /// from `query_unbatched_send_to_ethereums` for each of `senders`, and the reserve from the bank balance of
+    /// [`SommGravityExt::module_account_address`].
+    ///
+    /// The module has no query over the whole unbatched pool, only one per sender, so the unbatched totals only
+    /// cover `senders`. Pass every sender with a pending send for them to be complete.
+    ///
+    /// `token_contract` may omit the `0x` prefix and is matched against stored contracts by address rather than
+    /// by string, so any casing works.
+    async fn reconcile_token(&self, token_contract: impl AsRef<str>, senders: &[impl AsRef<str>]) -> Result<TokenReconciliation>
+    where
+        Self: Sized;
+    /// Pages through [`SommGravityExt::query_signer_set_txs`] until the node reports no further pages,
+    /// failing if the connection's [`PageLimits`] are exceeded
+    async fn query_all_signer_set_txs(&self) -> Result<Vec<SignerSetTx>>;
+    /// Returns the confirmations for a batch whose `ethereum_signer` is not a member of the signer set that was
+    /// in effect when the batch was created (the signer set with the greatest height at or below the batch's
+    /// height). Such confirmations come from rotated out or jailed validators and do not count toward quorum.
+    async fn stale_confirmations(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<BatchTxConfirmation>>
+    where
+        Self: Sized;
+    /// Returns the signer set that was in effect when a batch was created, the set its confirmations must be
+    /// evaluated against rather than the latest one.
+    ///
+    /// A `BatchTx` doesn't record a signer set nonce. The linkage is through heights instead: the batch's
+    /// `height` is the block it was created in, and the set in effect then is the stored signer set with the
+    /// greatest `height` at or below it. Errors if the batch is not found or no such signer set is stored.
+    async fn query_batch_signer_set(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<SignerSetTx>
+    where
+        Self: Sized;
+    /// Returns the confirmations for a batch whose signature recovers to their claimed `ethereum_signer` over
+    /// the batch's checkpoint, as checked by [`verify_batch_confirmation`]. Anything counting confirmations
+    /// toward quorum should start from these rather than the raw query response.
+    async fn valid_batch_confirmations(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<BatchTxConfirmation>>
+    where
+        Self: Sized;
+    /// Resolves the cosmos denom of a send's ERC20 contract via `query_erc20_to_denom`. Results are cached per
+    /// endpoint since the mapping never changes once established.
+    async fn send_denom(&self, send: &SendToEthereum) -> Result<String>;
+    /// Returns `amount + bridge_fee` for a send in its resolved cosmos denom
+    async fn send_total(&self, send: &SendToEthereum) -> Result<Coin>;
+    /// Pages through [`SommGravityExt::query_contract_call_txs`] until the node reports no further pages,
+    /// failing if the connection's [`PageLimits`] are exceeded
+    async fn query_all_contract_call_txs(&self) -> Result<Vec<ContractCallTx>>;
+    /// Summarizes, for one orchestrator, how many of the batches, signer sets and contract calls currently
+    /// stored on chain it has confirmed and which it still needs to confirm. The outstanding lists come from
+    /// the `unsigned_*` queries and the totals from the `all_*` aggregators, all run concurrently.
+    async fn validator_confirmation_status(&self, orchestrator: impl AsRef<str>) -> Result<ConfirmationStatus>
+    where
+        Self: Sized;
+    /// Renders the amounts of each send in an unbatched sends response using the token's ERC20 decimals from
+    /// `query_denom_to_erc20_params`. Decimals are cached per contract, and amounts of tokens whose decimals
+    /// cannot be resolved are shown as raw integers.
+    async fn display_unbatched_sends(&self, response: &UnbatchedSendToEthereumsResponse) -> Result<Vec<SendDisplay>>;
+    /// Polls `query_delegate_keys_by_validator` every `poll_interval` until the validator's registered keys
+    /// match `ethereum_address` and `orchestrator_address`, returning them, or errors once `timeout` elapses.
+    /// The last poll is made at the deadline, so keys registered just before it are still seen.
+    async fn await_delegate_keys(
+        &self,
+        validator_address: impl AsRef<str>,
+        ethereum_address: impl AsRef<str>,
+        orchestrator_address: impl AsRef<str>,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<DelegateKeysByValidatorResponse>
+    where
+        Self: Sized;
+    /// Same as [`SommGravityExt::await_delegate_keys`], reading time and sleeping through `clock`
+    async fn await_delegate_keys_with_clock(
+        &self,
+        validator_address: impl AsRef<str>,
+        ethereum_address: impl AsRef<str>,
+        orchestrator_address: impl AsRef<str>,
+        timeout: Duration,
+        poll_interval: Duration,
+        clock: &dyn Clock,
+    ) -> Result<DelegateKeysByValidatorResponse>
+    where
+        Self: Sized;
+    /// Same as [`SommGravityExt::query_contract_call_tx`], taking the scope and nonce as an [`Invalidation`]
+    async fn query_contract_call_tx_by_invalidation(&self, invalidation: &Invalidation) -> Result<ContractCallTxResponse>;
+    /// Same as [`SommGravityExt::query_contract_call_tx_confirmations`], taking the scope and nonce as an
+    /// [`Invalidation`]
+    async fn query_contract_call_tx_confirmations_by_invalidation(
+        &self,
+        invalidation: &Invalidation,
+    ) -> Result<ContractCallTxConfirmationsResponse>;
+    /// Exports params, the latest signer set, all batches, all contract calls and delegate keys as a
+    /// [`GravityStateSnapshot`] using the default [`ExportOptions`]
+    #[cfg(feature = "serde")]
+    async fn export_state(&self) -> Result<GravityStateSnapshot>;
+    /// Same as [`SommGravityExt::export_state`] with explicit options. Sections are queried concurrently, up to
+    /// `max_concurrency` at a time, and a failed section is recorded in the snapshot's `errors` rather than
+    /// failing the export.
+    #[cfg(feature = "serde")]
+    async fn export_state_with(&self, options: &ExportOptions) -> Result<GravityStateSnapshot>;
+    /// Returns the module state as a single serializable snapshot, for migrations and audits.
+    ///
+    /// The gravity module has no genesis or module state query over gRPC, so this is
+    /// [`SommGravityExt::export_state`] composed from the individual queries, and it covers what those
+    /// queries expose: params, the latest signer set, all batches, all contract calls and delegate keys. Unlike
+    /// `export_state`, it fails if any section could not be exported, so a returned snapshot is always whole.
+    /// The reads are not taken at a single height, so a snapshot of a live chain can straddle blocks.
+    #[cfg(feature = "serde")]
+    async fn query_module_state(&self) -> Result<GravityStateSnapshot>;
+    /// Exports state as in [`SommGravityExt::export_state_with`] and writes it to `writer` as gzip compressed
+    /// JSON, returning the snapshot. Read it back with [`crate::snapshot::import_state`].
+    #[cfg(feature = "gzip")]
+    async fn export_state_gzip(&self, writer: &mut dyn Write, options: &ExportOptions) -> Result<GravityStateSnapshot>;
+    /// Streams params, the latest signer set, every batch and contract call with their confirmations, and
+    /// delegate keys to `writer` as newline delimited JSON, paging through the queries so memory use stays flat
+    /// regardless of chain size. Sections are queried sequentially. See [`crate::snapshot`] for the record
+    /// schema.
+    #[cfg(feature = "serde")]
+    async fn export_state_ndjson(&self, writer: &mut (dyn AsyncWrite + Unpin)) -> Result<()>;
+    /// Returns the `somm` bech32 address of the gravity module account, where bridged funds are held. The
+    /// address is derived locally from the module name and requires no query.
+    async fn module_account_address(&self) -> Result<String>;
+    /// Runs the cheap top level queries concurrently for a dashboard header. Totals are read from the
+    /// `count_total` of a single element page, and a failed query leaves its field `None`.
+    async fn query_overview(&self) -> Result<Overview>;
+    /// Same as [`SommGravityExt::query_all_batch_txs`], but each page request carries the time remaining until
+    /// `deadline` as its gRPC timeout and no further pages are requested once `deadline` has passed
+    async fn query_all_batch_txs_until(&self, deadline: Instant) -> Result<Vec<BatchTx>>;
+    /// Deadline aware variant of [`SommGravityExt::query_all_signer_set_txs`], see
+    /// [`SommGravityExt::query_all_batch_txs_until`]
+    async fn query_all_signer_set_txs_until(&self, deadline: Instant) -> Result<Vec<SignerSetTx>>;
+    /// Deadline aware variant of [`SommGravityExt::query_all_contract_call_txs`], see
+    /// [`SommGravityExt::query_all_batch_txs_until`]
+    async fn query_all_contract_call_txs_until(&self, deadline: Instant) -> Result<Vec<ContractCallTx>>;
+    /// Deadline aware variant of [`SommGravityExt::query_all_unbatched_send_to_ethereums`], see
+    /// [`SommGravityExt::query_all_batch_txs_until`]
+    async fn query_all_unbatched_send_to_ethereums_until(
+        &self,
+        sender_address: impl AsRef<str>,
+        deadline: Instant,
+    ) -> Result<Vec<SendToEthereum>>
+    where
+        Self: Sized;
+    /// Returns `(denom, erc20_contract)` pairs for the tokens the bridge currently references, sorted by denom.
+    /// The gravity module has no query listing every mapped token, so the contracts are gathered from the
+    /// outgoing batches and contract calls currently stored on chain and each is resolved with the cached
+    /// `query_erc20_to_denom`. Tokens with no outstanding batch or call are not included.
+    ///
+    /// Only the per contract lookups are cached. The list itself is not, since it changes whenever a batch or
+    /// call is created or pruned and a new token can be bridged at any time, while the endpoint caches are never
+    /// invalidated. Callers listing assets repeatedly should cache the result for as long as suits them.
+    async fn query_all_bridged_denoms(&self) -> Result<Vec<(String, String)>>;
+    /// Fetches the ERC20 deployment parameters for a cosmos denom as a validated [`DenomErc20Params`]. The
+    /// contract address is filled in from `query_denom_to_erc20` when the token has already been deployed;
+    /// it cannot be derived locally since it depends on the bridge contract's deployment nonce.
+    async fn query_denom_erc20_params(&self, denom: impl AsRef<str>) -> Result<DenomErc20Params>
+    where
+        Self: Sized;
+    /// Cancels an unbatched send and recreates it with `new_bridge_fee` in a single tx, which is passed to
+    /// `submit_fn` for signing and broadcast. The original recipient and amount are looked up first so the
+    /// resend preserves them. Errors if the send has already been batched or the new fee is not higher.
+    async fn resend_transfer<F, Fut, R>(
+        &self,
+        sender: impl AsRef<str>,
+        send_id: u64,
+        new_bridge_fee: Coin,
+        submit_fn: F,
+    ) -> Result<R>
+    where
+        Self: Sized,
+        F: FnOnce(UnsignedTx) -> Fut,
+        Fut: Future<Output = Result<R>>;
+    /// Returns the 32 byte `gravity_id` used in checkpoints, parsed from the module [`Params`] with
+    /// [`parse_gravity_id`]. The id is fixed for the life of a deployment, so it is cached per endpoint.
+    async fn query_gravity_id(&self) -> Result<[u8; 32]>;
+    /// Returns the address of the bridge contract on Ethereum from the module [`Params`]. The address is fixed
+    /// for the life of a deployment, so it is cached per endpoint. An empty or malformed address errors, since
+    /// it means the endpoint's module is misconfigured or not a gravity module at all.
+    async fn bridge_contract_address(&self) -> Result<EthAddress>;
+    /// Returns the Ethereum block height at which a pending batch times out.
+    ///
+    /// The module has no message to cancel or replace a batch once it has been requested. The only way to
+    /// unstick one is to wait out its timeout: once the Ethereum height the validators vote for passes it, the
+    /// module deletes the batch and returns its sends to the unbatched pool. From there they can be cancelled
+    /// with [`SommGravity::CancelSendToEthereum`], resent with a higher fee with
+    /// [`SommGravityExt::resend_transfer`], or picked up by the next [`SommGravity::RequestBatchTx`].
+    async fn batch_timeout(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<u64>
+    where
+        Self: Sized;
+    /// Returns the signer of each of a batch's confirmations with the block height its confirmation was
+    /// submitted at, ordered by height, to reconstruct how quorum formed.
+    ///
+    /// Confirmations carry no submission height and the gravity queries don't expose tx indexing, so heights are
+    /// found by bisecting historical confirmation queries, as [`SommGravityExt::batch_quorum_time`] does, with
+    /// one search shared by every signer. A signer's height is `None` when the node couldn't answer at the
+    /// heights needed to place it, typically because it has pruned that state. Confirmations with a malformed
+    /// `ethereum_signer` are omitted.
+    async fn confirmation_timeline(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<(EthAddress, Option<u64>)>>
+    where
+        Self: Sized;
+    /// Fetches the signer sets, batches and contract calls `orchestrator` has yet to sign, concurrently
+    async fn query_pending_work(&self, orchestrator: impl AsRef<str>) -> Result<PendingWork>
+    where
+        Self: Sized;
+    /// Runs [`SommGravityExt::query_pending_work`] for each orchestrator, with at most `max_concurrency`
+    /// orchestrators (three streams each) in flight at once. Results are returned in the order of
+    /// `orchestrators`, and a failure for one orchestrator is reported in its entry without affecting the others.
+    async fn query_pending_work_many(
+        &self,
+        orchestrators: &[&str],
+        max_concurrency: usize,
+    ) -> Result<Vec<(String, Result<PendingWork>)>>;
+    /// Splits a sender's transfers into those already in a batch and those still in the unbatched pool, querying
+    /// both concurrently. A send reported by both queries, as can happen if it is batched between the two
+    /// reads, is only returned as batched.
+    async fn classify_sends(&self, sender: impl AsRef<str>) -> Result<SendClassification>
+    where
+        Self: Sized;
+    /// Estimates when a batch's valid confirmations first exceeded the [`POWER_THRESHOLD`] of its signer set,
+    /// or returns `None` if they have not yet. Confirmations carry no submission time, so the first height at
+    /// which the threshold was met is found by binary searching historical confirmation queries between the
+    /// batch's height and the current height, using the `x-cosmos-block-height` request header.
+    ///
+    /// The result is only an estimate. The timestamp is extrapolated back from the current time using the
+    /// module's `average_block_time` param, so it drifts with block time variance over the interval, and it
+    /// has at best block granularity. The search takes around `log2(current height - batch height)` queries
+    /// and fails against nodes that have pruned the state at the heights it probes.
+    ///
+    /// [`POWER_THRESHOLD`]: crate::confirmations::POWER_THRESHOLD
+    async fn batch_quorum_time(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Option<Timestamp>>
+    where
+        Self: Sized;
+    /// Tallies a batch's valid confirmations against the signer set in effect when the batch was created
+    async fn query_batch_readiness(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Readiness>
+    where
+        Self: Sized;
+    /// Tallies a signer set's valid confirmations against the signer set it replaces, whose signatures
+    /// `Gravity.sol` checks when the update is relayed. The first signer set is tallied against itself.
+    async fn query_signer_set_readiness(&self, nonce: u64) -> Result<Readiness>;
+    /// Tallies a contract call's valid confirmations against the signer set in effect when the call was created
+    async fn query_contract_call_readiness(&self, invalidation: &Invalidation) -> Result<Readiness>;
+    /// Returns the batches a relayer can submit now, sorted by nonce ascending: those whose timeout is above
+    /// `current_eth_height` and whose valid confirmations both exceed the bridge's power threshold and make up
+    /// at least `min_power_fraction` of their signer set's power.
+    ///
+    /// The module prunes a token's stored batches once it observes a batch for that token executed, so every
+    /// stored batch already has a nonce above the last observed execution. Use
+    /// [`SommGravityExt::relayable_batches_after`] to also skip batches made stale by executions on Ethereum
+    /// that the chain has not observed yet.
+    async fn relayable_batches(&self, current_eth_height: u64, min_power_fraction: f64) -> Result<Vec<BatchTx>>;
+    /// Same as [`SommGravityExt::relayable_batches`], additionally skipping batches whose nonce is at or below
+    /// the last executed nonce for their token in `last_executed_nonces`, as read from the bridge contract
+    ///
+    /// Batches whose stored token contract doesn't parse are skipped rather than failing the scan.
+    async fn relayable_batches_after(
+        &self,
+        current_eth_height: u64,
+        min_power_fraction: f64,
+        last_executed_nonces: &BTreeMap<EthAddress, u64>,
+    ) -> Result<Vec<BatchTx>>;
+    /// Returns each batch from [`SommGravityExt::relayable_batches`] with its [`batch_total_fees`], the fees a
+    /// relayer would earn, to weigh against the gas cost of relaying it. Fees are in the token's cosmos denom as
+    /// resolved, and cached, with `query_erc20_to_denom`.
+    async fn relayable_batch_fees(
+        &self,
+        current_eth_height: u64,
+        min_power_fraction: f64,
+    ) -> Result<Vec<(BatchTx, Coin)>>;
+    /// Polls [`SommGravityExt::relayable_batches`] every `poll_interval` and yields each batch once, as soon as
+    /// it becomes relayable. `current_height_fn` supplies the current Ethereum height on every poll, so the
+    /// crate needs no Ethereum client of its own. A failed poll is yielded as an error and polling continues.
+    ///
+    /// Yielded batches are remembered until they time out, so a batch is never yielded twice.
+    fn relay_ready_batches_stream<'a, F, Fut>(
+        &'a self,
+        poll_interval: Duration,
+        min_power_fraction: f64,
+        current_height_fn: F,
+    ) -> LocalBoxStream<'a, Result<BatchTx>>
+    where
+        Self: Sized,
+        F: FnMut() -> Fut + 'a,
+        Fut: Future<Output = Result<u64>> + 'a;
+    /// Same as [`SommGravityExt::relay_ready_batches_stream`], sleeping between polls through `clock`
+    fn relay_ready_batches_stream_with_clock<'a, F, Fut>(
+        &'a self,
+        poll_interval: Duration,
+        min_power_fraction: f64,
+        current_height_fn: F,
+        clock: &'a dyn Clock,
+    ) -> LocalBoxStream<'a, Result<BatchTx>>
+    where
+        Self: Sized,
+        F: FnMut() -> Fut + 'a,
+        Fut: Future<Output = Result<u64>> + 'a;
+    /// Fetches the confirmations of each `(token_contract, nonce)` batch, with at most `max_concurrency`
+    /// queries in flight. By default the first failed query fails the whole call. With `partial_ok` set, failed
+    /// queries are instead reported in [`BatchConfirmationsMany::failed`] alongside the ones that succeeded.
+    async fn query_batch_tx_confirmations_many(
+        &self,
+        batches: &[(&str, u64)],
+        max_concurrency: usize,
+        partial_ok: bool,
+    ) -> Result<BatchConfirmationsMany>;
+    /// Runs [`SommGravityExt::query_batch_tx_confirmations_many`] over every stored batch. Failing to list the
+    /// batches is an error even with `partial_ok` set.
+    async fn query_all_batch_confirmations(&self, max_concurrency: usize, partial_ok: bool) -> Result<BatchConfirmationsMany>;
+    /// Returns the orchestrators with delegate keys set that have never submitted an Ethereum event, which
+    /// usually means the validator isn't running its orchestrator or has misconfigured it. The per orchestrator
+    /// lookups run with at most `max_concurrency` in flight, and any failed lookup fails the call.
+    async fn silent_orchestrators(&self, max_concurrency: usize) -> Result<Vec<SilentOrchestrator>>;
+    /// Checks that the last `count` stored signer sets, in the order the module returns them, have strictly
+    /// increasing nonces and heights, returning a [`NonMonotonicSignerSets`] error naming each set that breaks
+    /// the order. A regression points at a reorg or corrupted node state.
+    ///
+    /// This is a consistency check of what one node reports, not a consensus guarantee: a node that is itself
+    /// serving bad state can pass it.
+    async fn assert_monotonic_signer_sets(&self, count: usize) -> Result<()>;
+    /// Fetches a batch and the `gravity_id` and signs the batch with [`sign_batch_confirmation`]. To sign many
+    /// confirmations, fetch the id once and call the signing helpers directly instead.
+    async fn fetch_and_sign_batch_confirmation(
+        &self,
+        key: &SigningKey,
+        token_contract: impl AsRef<str>,
+        nonce: u64,
+    ) -> Result<BatchTxConfirmation>
+    where
+        Self: Sized;
+    /// Fetches a signer set and the `gravity_id` and signs the set with [`sign_signer_set_confirmation`]
+    async fn fetch_and_sign_signer_set_confirmation(&self, key: &SigningKey, nonce: u64) -> Result<SignerSetTxConfirmation>;
+    /// Fetches a contract call and the `gravity_id` and signs the call with [`sign_contract_call_confirmation`]
+    async fn fetch_and_sign_contract_call_confirmation(
+        &self,
+        key: &SigningKey,
+        invalidation: &Invalidation,
+    ) -> Result<ContractCallTxConfirmation>;
 }
 
 #[async_trait(?Send)]
-impl SommGravityExt for GrpcClient {
+impl<T: GravityConnection> SommGravityExt for T {
     async fn query_somm_gravity_params(&self) -> Result<ParamsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = ParamsRequest {};
 
-        Ok(client.inner.params(request).await?.into_inner())
+        wire_log::request("params", &request);
+        let response = client.inner.params(request).await.map_err(|e| map_status("params", e))?.into_inner();
+        wire_log::response("params", &response);
+
+        Ok(response)
+    }
+
+    async fn is_bridge_active(&self) -> Result<bool> {
+        match self.query_somm_gravity_params().await?.params {
+            Some(params) => Ok(params.bridge_active),
+            None => bail!("params response did not contain params"),
+        }
     }
 
     async fn query_signer_set_tx(&self, nonce: u64) -> Result<SignerSetTxResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        validate_nonce(nonce)?;
+        let mut client = self.gravity_client().await?;
         let request = SignerSetTxRequest {
             signer_set_nonce: nonce,
         };
 
-        Ok(client.inner.signer_set_tx(request).await?.into_inner())
+        wire_log::request("signer_set_tx", &request);
+        let response = client.inner.signer_set_tx(request).await.map_err(|e| map_status("signer_set_tx", e))?.into_inner();
+        wire_log::response("signer_set_tx", &response);
+
+        Ok(response)
     }
 
     async fn query_latest_signer_set_tx(&self) -> Result<SignerSetTxResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = LatestSignerSetTxRequest {};
 
-        Ok(client.inner.latest_signer_set_tx(request).await?.into_inner())
+        wire_log::request("latest_signer_set_tx", &request);
+        let response = client.inner.latest_signer_set_tx(request).await.map_err(|e| map_status("latest_signer_set_tx", e))?.into_inner();
+        wire_log::response("latest_signer_set_tx", &response);
+
+        Ok(response)
     }
 
-    async fn query_batch_tx(&self, token_contract_address: &str, nonce: u64) -> Result<BatchTxResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+    async fn query_latest_signer_set_confirmations(&self) -> Result<SignerSetConfirmations> {
+        let signer_set = latest_signer_set(self).await?;
+        let confirmations = self
+            .query_signer_set_tx_confirmations(signer_set.nonce)
+            .await?
+            .signatures;
+
+        let current = latest_signer_set(self).await?;
+        if current.nonce == signer_set.nonce {
+            return Ok(SignerSetConfirmations {
+                signer_set,
+                confirmations,
+            });
+        }
+
+        let confirmations = self
+            .query_signer_set_tx_confirmations(current.nonce)
+            .await?
+            .signatures;
+
+        Ok(SignerSetConfirmations {
+            signer_set: current,
+            confirmations,
+        })
+    }
+
+    async fn query_batch_tx(&self, token_contract_address: impl AsRef<str>, nonce: u64) -> Result<BatchTxResponse> {
+        let token_contract_address = token_contract_address.as_ref();
+        validate_nonce(nonce)?;
+        let token_contract = normalize_contract(token_contract_address)?;
+        let mut client = self.gravity_client().await?;
         let request = BatchTxRequest {
-            token_contract: token_contract_address.to_string(),
+            token_contract,
             batch_nonce: nonce,
         };
 
-        Ok(client.inner.batch_tx(request).await?.into_inner())
+        wire_log::request("batch_tx", &request);
+        let response = client.inner.batch_tx(request).await.map_err(|e| map_status("batch_tx", e))?.into_inner();
+        wire_log::response("batch_tx", &response);
+
+        Ok(response)
     }
 
     async fn query_contract_call_tx(&self, invalidation_scope: Vec<u8>, invalidation_nonce: u64) -> Result<ContractCallTxResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = ContractCallTxRequest {
             invalidation_scope,
             invalidation_nonce,
         };
 
-        Ok(client.inner.contract_call_tx(request).await?.into_inner())
+        wire_log::request("contract_call_tx", &request);
+        let response = client.inner.contract_call_tx(request).await.map_err(|e| map_status("contract_call_tx", e))?.into_inner();
+        wire_log::response("contract_call_tx", &response);
+
+        Ok(response)
     }
 
     async fn query_signer_set_txs(
         &self,
         pagination: Option<PageRequest>,
     ) -> Result<SignerSetTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = SignerSetTxsRequest {
             pagination,
         };
 
-        Ok(client.inner.signer_set_txs(request).await?.into_inner())
+        wire_log::request("signer_set_txs", &request);
+        let response = client.inner.signer_set_txs(request).await.map_err(|e| map_status("signer_set_txs", e))?.into_inner();
+        wire_log::response("signer_set_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_batch_txs(&self, pagination: Option<PageRequest>) -> Result<BatchTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = BatchTxsRequest {
             pagination,
         };
 
-        Ok(client.inner.batch_txs(request).await?.into_inner())
+        wire_log::request("batch_txs", &request);
+        let response = client.inner.batch_txs(request).await.map_err(|e| map_status("batch_txs", e))?.into_inner();
+        wire_log::response("batch_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_contract_call_txs(
         &self,
         pagination: Option<PageRequest>,
     ) -> Result<ContractCallTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = ContractCallTxsRequest {
             pagination,
         };
 
-        Ok(client.inner.contract_call_txs(request).await?.into_inner())
+        wire_log::request("contract_call_txs", &request);
+        let response = client.inner.contract_call_txs(request).await.map_err(|e| map_status("contract_call_txs", e))?.into_inner();
+        wire_log::response("contract_call_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_signer_set_tx_confirmations(
         &self,
         nonce: u64,
     ) -> Result<SignerSetTxConfirmationsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        validate_nonce(nonce)?;
+        let mut client = self.gravity_client().await?;
         let request = SignerSetTxConfirmationsRequest {
             signer_set_nonce: nonce,
         };
 
-        Ok(client.inner.signer_set_tx_confirmations(request).await?.into_inner())
+        wire_log::request("signer_set_tx_confirmations", &request);
+        let response = client.inner.signer_set_tx_confirmations(request).await.map_err(|e| map_status("signer_set_tx_confirmations", e))?.into_inner();
+        wire_log::response("signer_set_tx_confirmations", &response);
+
+        Ok(response)
     }
 
     async fn query_batch_tx_confirmations(
         &self,
         nonce: u64,
-        token_contract_address: &str,
+        token_contract_address: impl AsRef<str>,
     ) -> Result<BatchTxConfirmationsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let token_contract_address = token_contract_address.as_ref();
+        validate_nonce(nonce)?;
+        let token_contract = normalize_contract(token_contract_address)?;
+        let mut client = self.gravity_client().await?;
         let request = BatchTxConfirmationsRequest {
-            token_contract: token_contract_address.to_string(),
+            token_contract,
             batch_nonce: nonce,
         };
 
-        Ok(client.inner.batch_tx_confirmations(request).await?.into_inner())
+        wire_log::request("batch_tx_confirmations", &request);
+        let response = client.inner.batch_tx_confirmations(request).await.map_err(|e| map_status("batch_tx_confirmations", e))?.into_inner();
+        wire_log::response("batch_tx_confirmations", &response);
+
+        Ok(response)
     }
 
     async fn query_contract_call_tx_confirmations(
@@ -195,416 +794,1983 @@ impl SommGravityExt for GrpcClient {
         invalidation_scope: Vec<u8>,
         invalidation_nonce: u64,
     ) -> Result<ContractCallTxConfirmationsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = ContractCallTxConfirmationsRequest {
             invalidation_scope,
             invalidation_nonce,
         };
 
-        Ok(client.inner.contract_call_tx_confirmations(request).await?.into_inner())
+        wire_log::request("contract_call_tx_confirmations", &request);
+        let response = client.inner.contract_call_tx_confirmations(request).await.map_err(|e| map_status("contract_call_tx_confirmations", e))?.into_inner();
+        wire_log::response("contract_call_tx_confirmations", &response);
+
+        Ok(response)
     }
 
     async fn query_unsigned_signer_set_txs(
         &self,
-        address: &str,
+        address: impl AsRef<str>,
     ) -> Result<UnsignedSignerSetTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let address = address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = UnsignedSignerSetTxsRequest {
             address: address.to_string(),
         };
 
-        Ok(client.inner.unsigned_signer_set_txs(request).await?.into_inner())
+        wire_log::request("unsigned_signer_set_txs", &request);
+        let response = client.inner.unsigned_signer_set_txs(request).await.map_err(|e| map_status("unsigned_signer_set_txs", e))?.into_inner();
+        wire_log::response("unsigned_signer_set_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_unsigned_batch_txs(
         &self,
-        address: &str,
+        address: impl AsRef<str>,
     ) -> Result<UnsignedBatchTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let address = address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = UnsignedBatchTxsRequest {
             address: address.to_string(),
         };
 
-        Ok(client.inner.unsigned_batch_txs(request).await?.into_inner())
+        wire_log::request("unsigned_batch_txs", &request);
+        let response = client.inner.unsigned_batch_txs(request).await.map_err(|e| map_status("unsigned_batch_txs", e))?.into_inner();
+        wire_log::response("unsigned_batch_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_unsigned_contract_call_txs(
         &self,
-        address: &str,
+        address: impl AsRef<str>,
     ) -> Result<UnsignedContractCallTxsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let address = address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = UnsignedContractCallTxsRequest {
             address: address.to_string(),
         };
 
-        Ok(client.inner.unsigned_contract_call_txs(request).await?.into_inner())
+        wire_log::request("unsigned_contract_call_txs", &request);
+        let response = client.inner.unsigned_contract_call_txs(request).await.map_err(|e| map_status("unsigned_contract_call_txs", e))?.into_inner();
+        wire_log::response("unsigned_contract_call_txs", &response);
+
+        Ok(response)
     }
 
     async fn query_last_submitted_ethereum_event(
         &self,
-        address: &str,
+        address: impl AsRef<str>,
     ) -> Result<LastSubmittedEthereumEventResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let address = address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = LastSubmittedEthereumEventRequest {
             address: address.to_string(),
         };
 
-        Ok(client.inner.last_submitted_ethereum_event(request).await?.into_inner())
+        wire_log::request("last_submitted_ethereum_event", &request);
+        let response = client.inner.last_submitted_ethereum_event(request).await.map_err(|e| map_status("last_submitted_ethereum_event", e))?.into_inner();
+        wire_log::response("last_submitted_ethereum_event", &response);
+
+        Ok(response)
     }
 
-    async fn query_erc20_to_denom(&self, erc20: &str) -> Result<String> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+    async fn query_erc20_to_denom(&self, erc20: impl AsRef<str>) -> Result<String> {
+        let erc20 = erc20.as_ref();
+        let erc20 = normalize_contract(erc20)?;
+        let mut client = self.gravity_client().await?;
         let request = Erc20ToDenomRequest {
-            erc20: erc20.to_string(),
+            erc20,
         };
 
-        Ok(client.inner.erc20_to_denom(request).await?.into_inner().denom)
+        wire_log::request("erc20_to_denom", &request);
+        let response = client.inner.erc20_to_denom(request).await.map_err(|e| map_status("erc20_to_denom", e))?.into_inner();
+        wire_log::response("erc20_to_denom", &response);
+
+        Ok(response.denom)
     }
 
-    async fn query_denom_to_erc20_params(&self, denom: &str) -> Result<DenomToErc20ParamsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+    async fn query_denom_to_erc20_params(&self, denom: impl AsRef<str>) -> Result<DenomToErc20ParamsResponse> {
+        let denom = denom.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = DenomToErc20ParamsRequest {
             denom: denom.to_string(),
         };
 
-        Ok(client.inner.denom_to_erc20_params(request).await?.into_inner())
+        wire_log::request("denom_to_erc20_params", &request);
+        let response = client.inner.denom_to_erc20_params(request).await.map_err(|e| map_status("denom_to_erc20_params", e))?.into_inner();
+        wire_log::response("denom_to_erc20_params", &response);
+
+        Ok(response)
     }
 
-    async fn query_denom_to_erc20(&self, denom: &str) -> Result<String> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+    async fn query_denom_to_erc20(&self, denom: impl AsRef<str>) -> Result<String> {
+        let denom = denom.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = DenomToErc20Request {
             denom: denom.to_string(),
         };
 
-        Ok(client.inner.denom_to_erc20(request).await?.into_inner().erc20)
+        wire_log::request("denom_to_erc20", &request);
+        let response = client.inner.denom_to_erc20(request).await.map_err(|e| map_status("denom_to_erc20", e))?.into_inner();
+        wire_log::response("denom_to_erc20", &response);
+
+        Ok(response.erc20)
     }
 
     async fn query_delegate_keys_by_validator(
         &self,
-        validator_address: &str,
+        validator_address: impl AsRef<str>,
     ) -> Result<DelegateKeysByValidatorResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let validator_address = validator_address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = DelegateKeysByValidatorRequest {
             validator_address: validator_address.to_string(),
         };
 
-        Ok(client.inner.delegate_keys_by_validator(request).await?.into_inner())
+        wire_log::request("delegate_keys_by_validator", &request);
+        let response = client.inner.delegate_keys_by_validator(request).await.map_err(|e| map_status("delegate_keys_by_validator", e))?.into_inner();
+        wire_log::response("delegate_keys_by_validator", &response);
+
+        Ok(response)
     }
 
     async fn query_delegate_keys_by_ethereum_signer(
         &self,
-        ethereum_signer_address: &str,
+        ethereum_signer_address: impl AsRef<str>,
     ) -> Result<DelegateKeysByEthereumSignerResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let ethereum_signer_address = ethereum_signer_address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = DelegateKeysByEthereumSignerRequest {
             ethereum_signer: ethereum_signer_address.to_string(),
         };
 
-        Ok(client.inner.delegate_keys_by_ethereum_signer(request).await?.into_inner())
+        wire_log::request("delegate_keys_by_ethereum_signer", &request);
+        let response = client.inner.delegate_keys_by_ethereum_signer(request).await.map_err(|e| map_status("delegate_keys_by_ethereum_signer", e))?.into_inner();
+        wire_log::response("delegate_keys_by_ethereum_signer", &response);
+
+        Ok(response)
     }
 
     async fn query_delegate_keys_by_orchestrator(
         &self,
-        orchestrator_address: &str,
+        orchestrator_address: impl AsRef<str>,
     ) -> Result<DelegateKeysByOrchestratorResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let orchestrator_address = orchestrator_address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = DelegateKeysByOrchestratorRequest {
             orchestrator_address: orchestrator_address.to_string(),
         };
 
-        Ok(client.inner.delegate_keys_by_orchestrator(request).await?.into_inner())
+        wire_log::request("delegate_keys_by_orchestrator", &request);
+        let response = client.inner.delegate_keys_by_orchestrator(request).await.map_err(|e| map_status("delegate_keys_by_orchestrator", e))?.into_inner();
+        wire_log::response("delegate_keys_by_orchestrator", &response);
+
+        Ok(response)
     }
 
     async fn query_delegate_keys(&self) -> Result<DelegateKeysResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let mut client = self.gravity_client().await?;
         let request = DelegateKeysRequest {};
 
-        Ok(client.inner.delegate_keys(request).await?.into_inner())
+        wire_log::request("delegate_keys", &request);
+        let response = client.inner.delegate_keys(request).await.map_err(|e| map_status("delegate_keys", e))?.into_inner();
+        wire_log::response("delegate_keys", &response);
+
+        Ok(response)
     }
 
     async fn query_batched_send_to_ethereums(
         &self,
-        sender_address: &str,
+        sender_address: impl AsRef<str>,
     ) -> Result<BatchedSendToEthereumsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let sender_address = sender_address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = BatchedSendToEthereumsRequest {
             sender_address: sender_address.to_string(),
         };
 
-        Ok(client.inner.batched_send_to_ethereums(request).await?.into_inner())
+        wire_log::request("batched_send_to_ethereums", &request);
+        let response = client.inner.batched_send_to_ethereums(request).await.map_err(|e| map_status("batched_send_to_ethereums", e))?.into_inner();
+        wire_log::response("batched_send_to_ethereums", &response);
+
+        Ok(response)
     }
 
     async fn query_unbatched_send_to_ethereums(
         &self,
-        sender_address: &str,
+        sender_address: impl AsRef<str>,
         pagination: Option<PageRequest>,
     ) -> Result<UnbatchedSendToEthereumsResponse> {
-        let mut client = SommGravityQueryClient::new_client(self.grpc_endpoint()).await?;
+        let sender_address = sender_address.as_ref();
+        let mut client = self.gravity_client().await?;
         let request = UnbatchedSendToEthereumsRequest {
             sender_address: sender_address.to_string(),
             pagination,
         };
 
-        Ok(client.inner.unbatched_send_to_ethereums(request).await?.into_inner())
+        wire_log::request("unbatched_send_to_ethereums", &request);
+        let response = client.inner.unbatched_send_to_ethereums(request).await.map_err(|e| map_status("unbatched_send_to_ethereums", e))?.into_inner();
+        wire_log::response("unbatched_send_to_ethereums", &response);
+
+        Ok(response)
     }
-}
 
-pub enum SommGravity<'m> {
-    /// Represents a MsgSendToEthereum
-    SendToEthereum {
-        sender: &'m str,
-        ethereum_recipient: &'m str,
-        amount: Coin,
-        bridge_fee: Coin,
-    },
-    /// Represents a MsgCancelSendToEthereum
-    CancelSendToEthereum { sender: &'m str, id: u64 },
-    /// Represents a MsgRequestBatchTx
-    RequestBatchTx { denom: &'m str, signer: &'m str },
-    /// Represents a MsgSubmitEthereumTxConfirmation
-    SubmitEthereumTxConfirmation { confirmation: Any, signer: &'m str },
-    /// Represent a ContractCallTxConfirmation
-    ContractCallTxConfirmation {
-        invalidation_scope: Vec<u8>,
-        invalidation_nonce: u64,
-        ethereum_signer: &'m str,
-        signature: Vec<u8>,
-    },
-    /// Represents a BatchTxConfirmation
-    BatchTxConfirmation {
-        token_contract_address: &'m str,
-        batch_nonce: u64,
-        ethereum_signer: &'m str,
-        signature: Vec<u8>,
-    },
-    /// Represents a SignerSetTxConfirmation
-    SignerSetTxConfirmation {
-        signer_set_nonce: u64,
-        ethereum_signer: &'m str,
-        signature: Vec<u8>,
-    },
-    /// Represents a MsgSubmitEthereumEvent
-    SubmitEthereumEvent { event: Any, signer: &'m str },
-    /// Represents a MsgSetDelegateKeys
-    SetDelegateKeys {
-        validator_address: &'m str,
-        orchestrator_address: &'m str,
-        ethereum_address: &'m str,
-        eth_signature: Vec<u8>,
-    },
-    /// Represents a DelegateKeysMsg
-    DelegateKeysSignMsg {
-        validator_address: &'m str,
-        nonce: u64,
-    },
-    /// Represents a MsgSubmitEthereumHeightVote
-    SubmitEthereumHeightVote {
-        ethereum_height: u64,
-        signer: &'m str,
-    },
-}
+    async fn query_all_batch_txs(&self) -> Result<Vec<BatchTx>> {
+        collect_pages("batch_txs", self.page_limits(), |key| async move {
+            let pagination = PageRequest {
+                key,
+                ..Default::default()
+            };
+            let response = self.query_batch_txs(Some(pagination)).await?;
+
+            Ok((response.batches, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+        })
+        .await
+    }
+
+    async fn query_all_unbatched_send_to_ethereums(&self, sender_address: impl AsRef<str>) -> Result<Vec<SendToEthereum>> {
+        let sender_address = sender_address.as_ref();
+        collect_pages("unbatched_send_to_ethereums", self.page_limits(), |key| async move {
+            let pagination = PageRequest {
+                key,
+                ..Default::default()
+            };
+            let response = self.query_unbatched_send_to_ethereums(sender_address, Some(pagination)).await?;
+
+            Ok((response.send_to_ethereums, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+        })
+        .await
+    }
+
+    async fn reconcile_token(&self, token_contract: impl AsRef<str>, senders: &[impl AsRef<str>]) -> Result<TokenReconciliation> {
+        let contract = parse_contract(token_contract.as_ref())?;
+        let token_contract = contract.to_checksum();
+        let denom = self.query_erc20_to_denom(&token_contract).await?;
+        let reserve = module_balance(self, &denom).await?;
+
+        let batches = self.query_all_batch_txs().await?;
+        let mut unbatched = Vec::new();
+        for sender in senders {
+            unbatched.extend(self.query_all_unbatched_send_to_ethereums(sender.as_ref()).await?);
+        }
+        let (batched, unbatched) = token_send_totals(&contract, &batches, &unbatched)?;
+
+        Ok(TokenReconciliation {
+            token_contract,
+            denom,
+            batched,
+            unbatched,
+            reserve,
+        })
+    }
+
+    async fn query_all_signer_set_txs(&self) -> Result<Vec<SignerSetTx>> {
+        collect_pages("signer_set_txs", self.page_limits(), |key| async move {
+            let pagination = PageRequest {
+                key,
+                ..Default::default()
+            };
+            let response = self.query_signer_set_txs(Some(pagination)).await?;
+
+            Ok((response.signer_sets, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+        })
+        .await
+    }
+
+    async fn stale_confirmations(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<BatchTxConfirmation>> {
+        let token_contract = token_contract.as_ref();
+        let signer_set = self.query_batch_signer_set(token_contract, nonce).await?;
+        let members = signer_set
+            .signers
+            .iter()
+            .filter_map(|s| s.ethereum_address.parse::<EthAddress>().ok())
+            .collect::<Vec<EthAddress>>();
+
+        let confirmations = self
+            .query_batch_tx_confirmations(nonce, token_contract)
+            .await?
+            .signatures;
+
+        Ok(confirmations
+            .into_iter()
+            .filter(|c| match c.ethereum_signer.parse::<EthAddress>() {
+                Ok(signer) => !members.contains(&signer),
+                Err(_) => true,
+            })
+            .collect())
+    }
+
+    async fn query_batch_signer_set(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<SignerSetTx> {
+        let token_contract = token_contract.as_ref();
+        let batch = match self.query_batch_tx(token_contract, nonce).await?.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+
+        batch_signer_set(self, &batch).await
+    }
+
+    async fn valid_batch_confirmations(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<BatchTxConfirmation>> {
+        let token_contract = token_contract.as_ref();
+        let (batch, gravity_id, confirmations) = try_join!(
+            self.query_batch_tx(token_contract, nonce),
+            self.query_gravity_id(),
+            self.query_batch_tx_confirmations(nonce, token_contract)
+        )?;
+        let batch = match batch.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+
+        let mut valid = Vec::new();
+        for confirmation in confirmations.signatures {
+            if verify_batch_confirmation(&batch, &gravity_id, &confirmation)? {
+                valid.push(confirmation);
+            }
+        }
+
+        Ok(valid)
+    }
 
-impl ModuleMsg for SommGravity<'_> {
-    type Error = Report;
+    async fn send_denom(&self, send: &SendToEthereum) -> Result<String> {
+        match send.erc20_token.as_ref() {
+            Some(token) => contract_denom(self, &token.contract).await,
+            None => bail!("send {} has no erc20 token", send.id),
+        }
+    }
+
+    async fn send_total(&self, send: &SendToEthereum) -> Result<Coin> {
+        let denom = self.send_denom(send).await?;
+
+        total_amount_plus_fee_in(send, &denom)
+    }
+
+    async fn query_all_contract_call_txs(&self) -> Result<Vec<ContractCallTx>> {
+        collect_pages("contract_call_txs", self.page_limits(), |key| async move {
+            let pagination = PageRequest {
+                key,
+                ..Default::default()
+            };
+            let response = self.query_contract_call_txs(Some(pagination)).await?;
+
+            Ok((response.calls, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+        })
+        .await
+    }
+
+    async fn validator_confirmation_status(&self, orchestrator: impl AsRef<str>) -> Result<ConfirmationStatus> {
+        let orchestrator = orchestrator.as_ref();
+        let (batches, signer_sets, calls, unsigned_batches, unsigned_signer_sets, unsigned_calls) = try_join!(
+            self.query_all_batch_txs(),
+            self.query_all_signer_set_txs(),
+            self.query_all_contract_call_txs(),
+            self.query_unsigned_batch_txs(orchestrator),
+            self.query_unsigned_signer_set_txs(orchestrator),
+            self.query_unsigned_contract_call_txs(orchestrator),
+        )?;
+
+        Ok(ConfirmationStatus {
+            batches: ConfirmationCounts::new(batches.len(), unsigned_batches.batches),
+            signer_sets: ConfirmationCounts::new(signer_sets.len(), unsigned_signer_sets.signer_sets),
+            contract_calls: ConfirmationCounts::new(calls.len(), unsigned_calls.calls),
+        })
+    }
+
+    async fn display_unbatched_sends(&self, response: &UnbatchedSendToEthereumsResponse) -> Result<Vec<SendDisplay>> {
+        let mut displays = Vec::with_capacity(response.send_to_ethereums.len());
+        for send in response.send_to_ethereums.iter() {
+            let token = match send.erc20_token.as_ref() {
+                Some(token) => token,
+                None => bail!("send {} has no erc20 token", send.id),
+            };
+            let amount = parse_erc20_amount(token)?;
+            let fee = match send.erc20_fee.as_ref() {
+                Some(fee) => parse_erc20_amount(fee)?,
+                None => 0,
+            };
 
-    /// Converts the enum into an [`Any`] for use in a transaction
-    fn into_any(self) -> Result<Any> {
-        match self {
-            SommGravity::SendToEthereum {
-                sender,
-                ethereum_recipient,
+            let (amount, bridge_fee) = match contract_decimals(self, &token.contract).await {
+                Some(decimals) => (format_amount(amount, decimals), format_amount(fee, decimals)),
+                None => (amount.to_string(), fee.to_string()),
+            };
+            displays.push(SendDisplay {
+                id: send.id,
+                sender: send.sender.clone(),
+                ethereum_recipient: send.ethereum_recipient.clone(),
+                token_contract: token.contract.clone(),
                 amount,
                 bridge_fee,
-            } => {
-                let msg = gravity_proto::gravity::MsgSendToEthereum {
-                    sender: sender.to_string(),
-                    ethereum_recipient: ethereum_recipient.to_string(),
-                    amount: Some(amount.into()),
-                    bridge_fee: Some(bridge_fee.into()),
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgSendToEthereum: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgSendToEthereum".to_string();
-                Ok(any)
-            },
-            SommGravity::CancelSendToEthereum { sender, id } => {
-                let msg = gravity_proto::gravity::MsgCancelSendToEthereum {
-                    sender: sender.to_string(),
-                    id,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgCancelSendToEthereum: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgCancelSendToEthereum".to_string();
-                Ok(any)
-            },
-            SommGravity::RequestBatchTx { denom, signer } => {
-                let msg = gravity_proto::gravity::MsgRequestBatchTx {
-                    denom: denom.to_string(),
-                    signer: signer.to_string(),
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgRequestBatchTx: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgRequestBatchTx".to_string();
-                Ok(any)
-            },
-            SommGravity::SubmitEthereumTxConfirmation {
-                confirmation,
-                signer,
-            } => {
-                let msg = gravity_proto::gravity::MsgSubmitEthereumTxConfirmation {
-                    confirmation: Some(confirmation),
-                    signer: signer.to_string(),
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgSubmitEthereumTxConfirmation: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgSubmitEthereumTxConfirmation".to_string();
-                Ok(any)
-            },
-            SommGravity::ContractCallTxConfirmation {
-                invalidation_scope,
-                invalidation_nonce,
-                ethereum_signer,
-                signature,
-            } => {
-                let msg = gravity_proto::gravity::ContractCallTxConfirmation {
-                    invalidation_scope,
-                    invalidation_nonce,
-                    ethereum_signer: ethereum_signer.to_string(),
-                    signature,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode ContractCallTxConfirmation: {}", e)
-                };
-                any.type_url = "/gravity.v1.ContractCallTxConfirmation".to_string();
-                Ok(any)
-            },
-            SommGravity::BatchTxConfirmation {
-                token_contract_address,
-                batch_nonce,
-                ethereum_signer,
-                signature,
-            } => {
-                let msg = gravity_proto::gravity::BatchTxConfirmation {
-                    token_contract: token_contract_address.to_string(),
-                    batch_nonce,
-                    ethereum_signer: ethereum_signer.to_string(),
-                    signature,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode BatchTxConfirmation: {}", e)
-                };
-                any.type_url = "/gravity.v1.BatchTxConfirmation".to_string();
-                Ok(any)
-            },
-            SommGravity::SignerSetTxConfirmation {
-                signer_set_nonce,
-                ethereum_signer,
-                signature,
-            } => {
-                let msg = gravity_proto::gravity::SignerSetTxConfirmation {
-                    signer_set_nonce,
-                    ethereum_signer: ethereum_signer.to_string(),
-                    signature,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode SignerSetTxConfirmation: {}", e)
-                };
-                any.type_url = "/gravity.v1.SignerSetTxConfirmation".to_string();
-                Ok(any)
-            },
-            SommGravity::SubmitEthereumEvent { event, signer } => {
-                let msg = gravity_proto::gravity::MsgSubmitEthereumEvent {
-                    event: Some(event),
-                    signer: signer.to_string(),
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgSubmitEthereumEvent: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgSubmitEthereumEvent".to_string();
-                Ok(any)
-            },
-            SommGravity::SetDelegateKeys { validator_address, orchestrator_address, ethereum_address, eth_signature } => {
-                let msg = gravity_proto::gravity::MsgDelegateKeys {
-                    validator_address: validator_address.to_string(),
-                    orchestrator_address: orchestrator_address.to_string(),
-                    ethereum_address: ethereum_address.to_string(),
-                    eth_signature,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgDelegateKeys: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgDelegateKeys".to_string();
-                Ok(any)
-            },
-            SommGravity::DelegateKeysSignMsg { validator_address, nonce } => {
-                let msg = gravity_proto::gravity::DelegateKeysSignMsg {
-                    validator_address: validator_address.to_string(),
-                    nonce,
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode DelegateKeysSignMsg: {}", e)
-                };
-                any.type_url = "/gravity.v1.DelegateKeysSignMsg".to_string();
-                Ok(any)
-            },
-            SommGravity::SubmitEthereumHeightVote { ethereum_height, signer } => {
-                let msg = gravity_proto::gravity::MsgEthereumHeightVote {
-                    ethereum_height,
-                    signer: signer.to_string(),
-                };
-                let mut any = Any::default();
-                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
-                    bail!("failed to encode MsgEthereumHeightVote: {}", e)
-                };
-                any.type_url = "/gravity.v1.MsgEthereumHeightVote".to_string();
-                Ok(any)
-            },
+            });
+        }
+
+        Ok(displays)
+    }
+
+    async fn await_delegate_keys(
+        &self,
+        validator_address: impl AsRef<str>,
+        ethereum_address: impl AsRef<str>,
+        orchestrator_address: impl AsRef<str>,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<DelegateKeysByValidatorResponse> {
+        self.await_delegate_keys_with_clock(
+            validator_address,
+            ethereum_address,
+            orchestrator_address,
+            timeout,
+            poll_interval,
+            &SystemClock,
+        )
+        .await
+    }
+
+    async fn await_delegate_keys_with_clock(
+        &self,
+        validator_address: impl AsRef<str>,
+        ethereum_address: impl AsRef<str>,
+        orchestrator_address: impl AsRef<str>,
+        timeout: Duration,
+        poll_interval: Duration,
+        clock: &dyn Clock,
+    ) -> Result<DelegateKeysByValidatorResponse> {
+        let validator_address = validator_address.as_ref();
+        let ethereum_address = ethereum_address.as_ref();
+        let orchestrator_address = orchestrator_address.as_ref();
+        let expected_ethereum_address = ethereum_address.parse::<EthAddress>()?;
+        let polled = poll_until(clock, timeout, poll_interval, || async move {
+            match self.query_delegate_keys_by_validator(validator_address).await {
+                Ok(keys) => {
+                    let ethereum_matches = keys
+                        .eth_address
+                        .parse::<EthAddress>()
+                        .map_or(false, |a| a == expected_ethereum_address);
+                    if ethereum_matches && keys.orchestrator_address == orchestrator_address {
+                        return Ok(keys);
+                    }
+                    Err(format!(
+                        "registered keys are ethereum {} and orchestrator {}",
+                        keys.eth_address, keys.orchestrator_address
+                    ))
+                }
+                Err(e) => Err(format!("query failed: {}", e)),
+            }
+        })
+        .await;
+
+        match polled {
+            Ok(keys) => Ok(keys),
+            Err(last) => bail!(
+                "timed out waiting for delegate keys of {} to be registered; {}",
+                validator_address,
+                last
+            ),
+        }
+    }
+
+    async fn query_contract_call_tx_by_invalidation(&self, invalidation: &Invalidation) -> Result<ContractCallTxResponse> {
+        self.query_contract_call_tx(invalidation.scope.clone(), invalidation.nonce).await
+    }
+
+    async fn query_contract_call_tx_confirmations_by_invalidation(
+        &self,
+        invalidation: &Invalidation,
+    ) -> Result<ContractCallTxConfirmationsResponse> {
+        self.query_contract_call_tx_confirmations(invalidation.scope.clone(), invalidation.nonce)
+            .await
+    }
+
+    #[cfg(feature = "serde")]
+    async fn export_state(&self) -> Result<GravityStateSnapshot> {
+        self.export_state_with(&ExportOptions::default()).await
+    }
+
+    #[cfg(feature = "serde")]
+    async fn export_state_with(&self, options: &ExportOptions) -> Result<GravityStateSnapshot> {
+        snapshot::export_state(self, options).await
+    }
+
+    #[cfg(feature = "serde")]
+    async fn query_module_state(&self) -> Result<GravityStateSnapshot> {
+        let snapshot = self.export_state().await?;
+        if let Some(error) = snapshot.errors.first() {
+            bail!(
+                "failed to export module state section {}: {}",
+                error.section,
+                error.error
+            )
         }
+
+        Ok(snapshot)
+    }
+
+    #[cfg(feature = "gzip")]
+    async fn export_state_gzip(&self, writer: &mut dyn Write, options: &ExportOptions) -> Result<GravityStateSnapshot> {
+        let snapshot = self.export_state_with(options).await?;
+        snapshot.write_gzip(writer)?;
+
+        Ok(snapshot)
+    }
+
+    #[cfg(feature = "serde")]
+    async fn export_state_ndjson(&self, writer: &mut (dyn AsyncWrite + Unpin)) -> Result<()> {
+        snapshot::export_state_ndjson(self, writer).await
+    }
+
+    async fn module_account_address(&self) -> Result<String> {
+        address::module_account_address(GRAVITY_MODULE_NAME, SOMMELIER_ACCOUNT_PREFIX)
+    }
+
+    async fn query_overview(&self) -> Result<Overview> {
+        let count_page = || PageRequest {
+            limit: 1,
+            count_total: true,
+            ..Default::default()
+        };
+        let (signer_set, bridge_active, batches, calls) = join!(
+            self.query_latest_signer_set_tx(),
+            self.is_bridge_active(),
+            self.query_batch_txs(Some(count_page())),
+            self.query_contract_call_txs(Some(count_page())),
+        );
+
+        Ok(Overview {
+            latest_signer_set_nonce: signer_set.ok().and_then(|r| r.signer_set).map(|s| s.nonce),
+            params_bridge_active: bridge_active.ok(),
+            total_batches: batches.ok().and_then(|r| r.pagination).map(|p| p.total),
+            total_contract_calls: calls.ok().and_then(|r| r.pagination).map(|p| p.total),
+        })
+    }
+
+    async fn query_all_batch_txs_until(&self, deadline: Instant) -> Result<Vec<BatchTx>> {
+        let client = self.gravity_client().await?;
+        let limits = self.page_limits().until(deadline);
+        collect_pages("batch_txs", limits, |key| {
+            let mut inner = client.inner.clone();
+            async move {
+                let request = deadline_request(
+                    BatchTxsRequest {
+                        pagination: Some(PageRequest {
+                            key,
+                            ..Default::default()
+                        }),
+                    },
+                    deadline,
+                )?;
+                wire_log::request("batch_txs", request.get_ref());
+                let response = inner.batch_txs(request).await.map_err(|e| map_status("batch_txs", e))?.into_inner();
+                wire_log::response("batch_txs", &response);
+
+                Ok((response.batches, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+            }
+        })
+        .await
+    }
+
+    async fn query_all_signer_set_txs_until(&self, deadline: Instant) -> Result<Vec<SignerSetTx>> {
+        let client = self.gravity_client().await?;
+        let limits = self.page_limits().until(deadline);
+        collect_pages("signer_set_txs", limits, |key| {
+            let mut inner = client.inner.clone();
+            async move {
+                let request = deadline_request(
+                    SignerSetTxsRequest {
+                        pagination: Some(PageRequest {
+                            key,
+                            ..Default::default()
+                        }),
+                    },
+                    deadline,
+                )?;
+                wire_log::request("signer_set_txs", request.get_ref());
+                let response = inner.signer_set_txs(request).await.map_err(|e| map_status("signer_set_txs", e))?.into_inner();
+                wire_log::response("signer_set_txs", &response);
+
+                Ok((response.signer_sets, response.pagination.map(|p| p.next_key).unwrap_or_default()))
+            }
+        })
+        .await
     }
 
-    /// Converts the message enum representation into an [`UnsignedTx`] containing the corresponding Msg
-    fn into_tx(self) -> Result<UnsignedTx> {
-        // Since we include some confirmation messages in the enum to make getting an Any to insert into SubmitEthereumEventConfirmation
-        // easier, we need to make sure we don't try to submit those directly in a transaction because it's guaranteed to fail.
-        Ok(match self {
-            SommGravity::ContractCallTxConfirmation {
-                invalidation_scope: _,
-                invalidation_nonce: _,
-                ethereum_signer: _,
-                signature: _,
-            } => bail!("ContractCallTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation"),
-            SommGravity::BatchTxConfirmation {
-                token_contract_address: _,
-                batch_nonce: _,
-                ethereum_signer: _,
-                signature: _,
-            } => bail!("BatchTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation"),
-            SommGravity::SignerSetTxConfirmation {
-                signer_set_nonce: _,
-                ethereum_signer: _,
-                signature: _,
-            } => {
-                bail!("SignerSetTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation")
+    async fn query_all_contract_call_txs_until(&self, deadline: Instant) -> Result<Vec<ContractCallTx>> {
+        let client = self.gravity_client().await?;
+        let limits = self.page_limits().until(deadline);
+        collect_pages("contract_call_txs", limits, |key| {
+            let mut inner = client.inner.clone();
+            async move {
+                let request = deadline_request(
+                    ContractCallTxsRequest {
+                        pagination: Some(PageRequest {
+                            key,
+                            ..Default::default()
+                        }),
+                    },
+                    deadline,
+                )?;
+                wire_log::request("contract_call_txs", request.get_ref());
+                let response = inner.contract_call_txs(request).await.map_err(|e| map_status("contract_call_txs", e))?.into_inner();
+                wire_log::response("contract_call_txs", &response);
+
+                Ok((response.calls, response.pagination.map(|p| p.next_key).unwrap_or_default()))
             }
-            _ => {
-                let mut tx = UnsignedTx::new();
-                tx.add_msg(self.into_any()?);
-                tx
+        })
+        .await
+    }
+
+    async fn query_all_unbatched_send_to_ethereums_until(
+        &self,
+        sender_address: impl AsRef<str>,
+        deadline: Instant,
+    ) -> Result<Vec<SendToEthereum>> {
+        let sender_address = sender_address.as_ref();
+        let client = self.gravity_client().await?;
+        let limits = self.page_limits().until(deadline);
+        collect_pages("unbatched_send_to_ethereums", limits, |key| {
+            let mut inner = client.inner.clone();
+            async move {
+                let request = deadline_request(
+                    UnbatchedSendToEthereumsRequest {
+                        sender_address: sender_address.to_string(),
+                        pagination: Some(PageRequest {
+                            key,
+                            ..Default::default()
+                        }),
+                    },
+                    deadline,
+                )?;
+                wire_log::request("unbatched_send_to_ethereums", request.get_ref());
+                let response = inner.unbatched_send_to_ethereums(request).await.map_err(|e| map_status("unbatched_send_to_ethereums", e))?.into_inner();
+                wire_log::response("unbatched_send_to_ethereums", &response);
+
+                Ok((response.send_to_ethereums, response.pagination.map(|p| p.next_key).unwrap_or_default()))
             }
         })
+        .await
+    }
+
+    async fn query_all_bridged_denoms(&self) -> Result<Vec<(String, String)>> {
+        let (batches, calls) = try_join!(self.query_all_batch_txs(), self.query_all_contract_call_txs())?;
+
+        let mut contracts = BTreeSet::new();
+        for batch in batches.iter() {
+            contracts.insert(normalize_contract(&batch.token_contract)?);
+        }
+        for call in calls.iter() {
+            for token in call.tokens.iter().chain(call.fees.iter()) {
+                contracts.insert(normalize_contract(&token.contract)?);
+            }
+        }
+
+        let mut denoms = Vec::with_capacity(contracts.len());
+        for contract in contracts {
+            let denom = contract_denom(self, &contract).await?;
+            denoms.push((denom, contract));
+        }
+        denoms.sort();
+
+        Ok(denoms)
+    }
+
+    async fn query_denom_erc20_params(&self, denom: impl AsRef<str>) -> Result<DenomErc20Params> {
+        let denom = denom.as_ref();
+        let (params, erc20) = join!(self.query_denom_to_erc20_params(denom), self.query_denom_to_erc20(denom));
+        let mut params = DenomErc20Params::try_from(params?)?;
+        params.erc20_contract = erc20.ok().and_then(|c| c.parse::<EthAddress>().ok());
+
+        Ok(params)
+    }
+
+    async fn resend_transfer<F, Fut, R>(
+        &self,
+        sender: impl AsRef<str>,
+        send_id: u64,
+        new_bridge_fee: Coin,
+        submit_fn: F,
+    ) -> Result<R>
+    where
+        F: FnOnce(UnsignedTx) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let sender = sender.as_ref();
+        let unbatched = self.query_all_unbatched_send_to_ethereums(sender).await?;
+        let send = match unbatched.into_iter().find(|s| s.id == send_id) {
+            Some(send) => send,
+            None => {
+                let batched = self.query_batched_send_to_ethereums(sender).await?;
+                if batched.send_to_ethereums.iter().any(|s| s.id == send_id) {
+                    bail!("send {} has already been batched and cannot be cancelled", send_id)
+                }
+                bail!("no unbatched send {} found for {}", send_id, sender)
+            }
+        };
+
+        let denom = self.send_denom(&send).await?;
+        let amount = match send.erc20_token.as_ref() {
+            Some(token) => parse_erc20_amount(token)?,
+            None => bail!("send {} has no erc20 token", send_id),
+        };
+        let old_fee = match send.erc20_fee.as_ref() {
+            Some(fee) => parse_erc20_amount(fee)?,
+            None => 0,
+        };
+        if new_bridge_fee.amount <= old_fee {
+            bail!(
+                "new bridge fee {} must be higher than the current fee {}",
+                new_bridge_fee.amount,
+                old_fee
+            )
+        }
+
+        let mut tx = UnsignedTx::new();
+        tx.add_msg(SommGravity::CancelSendToEthereum { sender, id: send_id }.into_any()?);
+        tx.add_msg(SommGravity::send_to_ethereum(
+            sender,
+            &send.ethereum_recipient,
+            amount,
+            &denom,
+            new_bridge_fee.amount,
+            new_bridge_fee.denom.as_ref(),
+        )?
+        .into_any()?);
+
+        submit_fn(tx).await
+    }
+
+    async fn query_gravity_id(&self) -> Result<[u8; 32]> {
+        let endpoint = self.gravity_endpoint();
+        if let Some(gravity_id) = GRAVITY_ID.get(&endpoint, "") {
+            return Ok(gravity_id);
+        }
+
+        let gravity_id = match self.query_somm_gravity_params().await?.params {
+            Some(params) => parse_gravity_id(&params.gravity_id)?,
+            None => bail!("params response did not contain params"),
+        };
+        GRAVITY_ID.insert(&endpoint, "", gravity_id);
+
+        Ok(gravity_id)
+    }
+
+    async fn confirmation_timeline(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Vec<(EthAddress, Option<u64>)>> {
+        let token_contract = normalize_contract(token_contract.as_ref())?;
+        let batch = match self.query_batch_tx(&token_contract, nonce).await?.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+        let (confirmations, current_height) = batch_confirmations_at(self, &token_contract, nonce, None).await?;
+
+        let mut timeline = Vec::new();
+        // Each range is (low, high, signers first seen in low..=high)
+        let mut ranges = vec![(batch.height, current_height, confirmation_signers(&confirmations))];
+        while let Some((low, high, signers)) = ranges.pop() {
+            if signers.is_empty() {
+                continue;
+            }
+            if low >= high {
+                timeline.extend(signers.into_iter().map(|signer| (signer, Some(high))));
+                continue;
+            }
+
+            let mid = low + (high - low) / 2;
+            match batch_confirmations_at(self, &token_contract, nonce, Some(mid)).await {
+                Ok((confirmations, _)) => {
+                    let present = confirmation_signers(&confirmations);
+                    let (early, late) = signers.into_iter().partition(|signer| present.contains(signer));
+                    ranges.push((low, mid, early));
+                    ranges.push((mid + 1, high, late));
+                }
+                Err(_) => timeline.extend(signers.into_iter().map(|signer| (signer, None))),
+            }
+        }
+        timeline.sort_by_key(|(_, height)| height.unwrap_or(u64::MAX));
+
+        Ok(timeline)
+    }
+
+    async fn batch_timeout(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<u64> {
+        let token_contract = token_contract.as_ref();
+        match self.query_batch_tx(token_contract, nonce).await?.batch {
+            Some(batch) => Ok(batch.timeout),
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        }
+    }
+
+    async fn bridge_contract_address(&self) -> Result<EthAddress> {
+        let endpoint = self.gravity_endpoint();
+        if let Some(address) = BRIDGE_CONTRACT.get(&endpoint, "") {
+            return Ok(address);
+        }
+
+        let address = match self.query_somm_gravity_params().await?.params {
+            Some(params) if params.bridge_ethereum_address.is_empty() => {
+                bail!("params bridge_ethereum_address is empty")
+            }
+            Some(params) => match params.bridge_ethereum_address.parse::<EthAddress>() {
+                Ok(address) => address,
+                Err(e) => bail!("params bridge_ethereum_address is malformed: {}", e),
+            },
+            None => bail!("params response did not contain params"),
+        };
+        BRIDGE_CONTRACT.insert(&endpoint, "", address);
+
+        Ok(address)
+    }
+
+    async fn query_pending_work(&self, orchestrator: impl AsRef<str>) -> Result<PendingWork> {
+        let orchestrator = orchestrator.as_ref();
+        let (signer_sets, batches, calls) = try_join!(
+            self.query_unsigned_signer_set_txs(orchestrator),
+            self.query_unsigned_batch_txs(orchestrator),
+            self.query_unsigned_contract_call_txs(orchestrator),
+        )?;
+
+        Ok(PendingWork {
+            signer_sets: signer_sets.signer_sets,
+            batches: batches.batches,
+            contract_calls: calls.calls,
+        })
+    }
+
+    async fn query_pending_work_many(
+        &self,
+        orchestrators: &[&str],
+        max_concurrency: usize,
+    ) -> Result<Vec<(String, Result<PendingWork>)>> {
+        if max_concurrency == 0 {
+            bail!("max_concurrency must be non-zero")
+        }
+
+        Ok(stream::iter(orchestrators.iter())
+            .map(|orchestrator| async move {
+                (orchestrator.to_string(), self.query_pending_work(orchestrator).await)
+            })
+            .buffered(max_concurrency)
+            .collect()
+            .await)
+    }
+
+    async fn classify_sends(&self, sender: impl AsRef<str>) -> Result<SendClassification> {
+        let sender = sender.as_ref();
+        let (batched, unbatched) = try_join!(
+            self.query_batched_send_to_ethereums(sender),
+            self.query_all_unbatched_send_to_ethereums(sender),
+        )?;
+        let batched = batched.send_to_ethereums;
+        let batched_ids = batched.iter().map(|s| s.id).collect::<BTreeSet<u64>>();
+        let unbatched = unbatched
+            .into_iter()
+            .filter(|s| !batched_ids.contains(&s.id))
+            .collect();
+
+        Ok(SendClassification { batched, unbatched })
+    }
+
+    async fn batch_quorum_time(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Option<Timestamp>> {
+        let token_contract = token_contract.as_ref();
+        let token_contract = normalize_contract(token_contract)?;
+        let batch = match self.query_batch_tx(&token_contract, nonce).await?.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+        let (signer_set, gravity_id, params) = try_join!(
+            batch_signer_set(self, &batch),
+            self.query_gravity_id(),
+            self.query_somm_gravity_params(),
+        )?;
+        let average_block_time = match params.params {
+            Some(params) => params.average_block_time,
+            None => bail!("params response did not contain params"),
+        };
+
+        let (confirmations, current_height) = batch_confirmations_at(self, &token_contract, nonce, None).await?;
+        if !batch_readiness(&batch, &gravity_id, &signer_set, &confirmations)?.is_ready() {
+            return Ok(None);
+        }
+
+        let mut low = batch.height;
+        let mut high = current_height;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (confirmations, _) = batch_confirmations_at(self, &token_contract, nonce, Some(mid)).await?;
+            if batch_readiness(&batch, &gravity_id, &signer_set, &confirmations)?.is_ready() {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let elapsed = Duration::from_millis(current_height.saturating_sub(high).saturating_mul(average_block_time));
+        let since_epoch = SystemTime::now()
+            .checked_sub(elapsed)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        Ok(Some(Timestamp {
+            seconds: since_epoch.as_secs() as i64,
+            nanos: since_epoch.subsec_nanos() as i32,
+        }))
+    }
+
+    async fn query_batch_readiness(&self, token_contract: impl AsRef<str>, nonce: u64) -> Result<Readiness> {
+        let token_contract = token_contract.as_ref();
+        let (batch, gravity_id, confirmations) = try_join!(
+            self.query_batch_tx(token_contract, nonce),
+            self.query_gravity_id(),
+            self.query_batch_tx_confirmations(nonce, token_contract),
+        )?;
+        let batch = match batch.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+        let signer_set = batch_signer_set(self, &batch).await?;
+
+        batch_readiness(&batch, &gravity_id, &signer_set, &confirmations.signatures)
+    }
+
+    async fn query_signer_set_readiness(&self, nonce: u64) -> Result<Readiness> {
+        let (signer_set, gravity_id, confirmations, signer_sets) = try_join!(
+            self.query_signer_set_tx(nonce),
+            self.query_gravity_id(),
+            self.query_signer_set_tx_confirmations(nonce),
+            self.query_all_signer_set_txs(),
+        )?;
+        let signer_set = match signer_set.signer_set {
+            Some(signer_set) => signer_set,
+            None => bail!("signer set {} not found", nonce),
+        };
+        let members = signer_sets
+            .into_iter()
+            .filter(|s| s.nonce < nonce)
+            .max_by_key(|s| s.nonce)
+            .unwrap_or_else(|| signer_set.clone());
+
+        let mut confirmed = Vec::new();
+        for confirmation in confirmations.signatures.iter() {
+            if verify_signer_set_confirmation(&signer_set, &gravity_id, confirmation)? {
+                confirmed.push(confirmation.ethereum_signer.parse::<EthAddress>()?);
+            }
+        }
+
+        Ok(Readiness::new(&members, &confirmed))
+    }
+
+    async fn query_contract_call_readiness(&self, invalidation: &Invalidation) -> Result<Readiness> {
+        let (call, gravity_id, confirmations) = try_join!(
+            self.query_contract_call_tx_by_invalidation(invalidation),
+            self.query_gravity_id(),
+            self.query_contract_call_tx_confirmations_by_invalidation(invalidation),
+        )?;
+        let call = match call.logic_call {
+            Some(call) => call,
+            None => bail!("contract call {} not found", invalidation),
+        };
+        let signer_set = signer_set_at_height(self, call.height).await?;
+
+        let mut confirmed = Vec::new();
+        for confirmation in confirmations.signatures.iter() {
+            if verify_contract_call_confirmation(&call, &gravity_id, confirmation)? {
+                confirmed.push(confirmation.ethereum_signer.parse::<EthAddress>()?);
+            }
+        }
+
+        Ok(Readiness::new(&signer_set, &confirmed))
+    }
+
+    async fn relayable_batches(&self, current_eth_height: u64, min_power_fraction: f64) -> Result<Vec<BatchTx>> {
+        self.relayable_batches_after(current_eth_height, min_power_fraction, &BTreeMap::new())
+            .await
+    }
+
+    async fn relayable_batches_after(
+        &self,
+        current_eth_height: u64,
+        min_power_fraction: f64,
+        last_executed_nonces: &BTreeMap<EthAddress, u64>,
+    ) -> Result<Vec<BatchTx>> {
+        if !(0.0..=1.0).contains(&min_power_fraction) {
+            bail!("min_power_fraction must be between 0 and 1, got {}", min_power_fraction)
+        }
+
+        let (batches, signer_sets, gravity_id) = try_join!(
+            self.query_all_batch_txs(),
+            self.query_all_signer_set_txs(),
+            self.query_gravity_id(),
+        )?;
+
+        let mut candidates = Vec::new();
+        for batch in batches {
+            if batch.timeout <= current_eth_height {
+                continue;
+            }
+            // A stored batch with a malformed contract can never be relayed, so it shouldn't stop the scan
+            let contract = match batch.token_contract.parse::<EthAddress>() {
+                Ok(contract) => contract,
+                Err(_) => continue,
+            };
+            if last_executed_nonces
+                .get(&contract)
+                .map_or(false, |nonce| batch.batch_nonce <= *nonce)
+            {
+                continue;
+            }
+            let signer_set = match signer_sets
+                .iter()
+                .filter(|s| s.height <= batch.height)
+                .max_by_key(|s| s.height)
+            {
+                Some(signer_set) => signer_set,
+                None => continue,
+            };
+            candidates.push((batch, signer_set));
+        }
+
+        let results = stream::iter(candidates.into_iter())
+            .map(|(batch, signer_set)| async move {
+                let confirmations = self
+                    .query_batch_tx_confirmations(batch.batch_nonce, &batch.token_contract)
+                    .await?
+                    .signatures;
+                Ok::<_, crate::Error>((batch, signer_set, confirmations))
+            })
+            .buffer_unordered(RELAYABLE_SCAN_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut relayable = Vec::new();
+        for result in results {
+            let (batch, signer_set, confirmations) = result?;
+            let readiness = batch_readiness(&batch, &gravity_id, signer_set, &confirmations)?;
+            if readiness.is_ready() && readiness.power_fraction() >= min_power_fraction {
+                relayable.push(batch);
+            }
+        }
+        relayable.sort_by_key(|b| b.batch_nonce);
+
+        Ok(relayable)
+    }
+
+    async fn relayable_batch_fees(
+        &self,
+        current_eth_height: u64,
+        min_power_fraction: f64,
+    ) -> Result<Vec<(BatchTx, Coin)>> {
+        let mut with_fees = Vec::new();
+        for batch in self.relayable_batches(current_eth_height, min_power_fraction).await? {
+            let denom = contract_denom(self, &batch.token_contract).await?;
+            let fees = batch_total_fees(&batch, &denom)?;
+            with_fees.push((batch, fees));
+        }
+
+        Ok(with_fees)
+    }
+
+    fn relay_ready_batches_stream<'a, F, Fut>(
+        &'a self,
+        poll_interval: Duration,
+        min_power_fraction: f64,
+        current_height_fn: F,
+    ) -> LocalBoxStream<'a, Result<BatchTx>>
+    where
+        F: FnMut() -> Fut + 'a,
+        Fut: Future<Output = Result<u64>> + 'a,
+    {
+        self.relay_ready_batches_stream_with_clock(poll_interval, min_power_fraction, current_height_fn, &SystemClock)
+    }
+
+    fn relay_ready_batches_stream_with_clock<'a, F, Fut>(
+        &'a self,
+        poll_interval: Duration,
+        min_power_fraction: f64,
+        current_height_fn: F,
+        clock: &'a dyn Clock,
+    ) -> LocalBoxStream<'a, Result<BatchTx>>
+    where
+        F: FnMut() -> Fut + 'a,
+        Fut: Future<Output = Result<u64>> + 'a,
+    {
+        relay_stream(poll_interval, clock, current_height_fn, move |height| {
+            self.relayable_batches(height, min_power_fraction)
+        })
+    }
+
+    async fn query_batch_tx_confirmations_many(
+        &self,
+        batches: &[(&str, u64)],
+        max_concurrency: usize,
+        partial_ok: bool,
+    ) -> Result<BatchConfirmationsMany> {
+        if max_concurrency == 0 {
+            bail!("max_concurrency must be non-zero")
+        }
+
+        let results = stream::iter(batches.iter())
+            .map(|(token_contract, nonce)| async move {
+                let result = self.query_batch_tx_confirmations(*nonce, *token_contract).await;
+                (token_contract.to_string(), *nonce, result)
+            })
+            .buffered(max_concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut many = BatchConfirmationsMany::default();
+        for (token_contract, batch_nonce, result) in results {
+            match result {
+                Ok(response) => many.confirmations.push(BatchConfirmations {
+                    token_contract,
+                    batch_nonce,
+                    confirmations: response.signatures,
+                }),
+                Err(e) if partial_ok => many.failed.push(BatchConfirmationsFailure {
+                    token_contract,
+                    batch_nonce,
+                    error: e.to_string(),
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(many)
+    }
+
+    async fn query_all_batch_confirmations(&self, max_concurrency: usize, partial_ok: bool) -> Result<BatchConfirmationsMany> {
+        let batches = self.query_all_batch_txs().await?;
+        let keys = batches
+            .iter()
+            .map(|b| (b.token_contract.as_str(), b.batch_nonce))
+            .collect::<Vec<_>>();
+
+        self.query_batch_tx_confirmations_many(&keys, max_concurrency, partial_ok)
+            .await
+    }
+
+    async fn fetch_and_sign_batch_confirmation(
+        &self,
+        key: &SigningKey,
+        token_contract: impl AsRef<str>,
+        nonce: u64,
+    ) -> Result<BatchTxConfirmation> {
+        let token_contract = token_contract.as_ref();
+        let (batch, gravity_id) = try_join!(self.query_batch_tx(token_contract, nonce), self.query_gravity_id())?;
+        let batch = match batch.batch {
+            Some(batch) => batch,
+            None => bail!("batch {} for {} not found", nonce, token_contract),
+        };
+
+        sign_batch_confirmation(key, &gravity_id, &batch)
+    }
+
+    async fn fetch_and_sign_signer_set_confirmation(&self, key: &SigningKey, nonce: u64) -> Result<SignerSetTxConfirmation> {
+        let (signer_set, gravity_id) = try_join!(self.query_signer_set_tx(nonce), self.query_gravity_id())?;
+        let signer_set = match signer_set.signer_set {
+            Some(signer_set) => signer_set,
+            None => bail!("signer set {} not found", nonce),
+        };
+
+        sign_signer_set_confirmation(key, &gravity_id, &signer_set)
+    }
+
+    async fn fetch_and_sign_contract_call_confirmation(
+        &self,
+        key: &SigningKey,
+        invalidation: &Invalidation,
+    ) -> Result<ContractCallTxConfirmation> {
+        let (call, gravity_id) = try_join!(
+            self.query_contract_call_tx_by_invalidation(invalidation),
+            self.query_gravity_id(),
+        )?;
+        let call = match call.logic_call {
+            Some(call) => call,
+            None => bail!("contract call {} not found", invalidation),
+        };
+
+        sign_contract_call_confirmation(key, &gravity_id, &call)
+    }
+
+    async fn assert_monotonic_signer_sets(&self, count: usize) -> Result<()> {
+        let signer_sets = self.query_all_signer_set_txs().await?;
+        let recent = &signer_sets[signer_sets.len().saturating_sub(count)..];
+        let nonces = recent
+            .windows(2)
+            .filter(|pair| pair[1].nonce <= pair[0].nonce || pair[1].height <= pair[0].height)
+            .map(|pair| pair[1].nonce)
+            .collect::<Vec<u64>>();
+        if !nonces.is_empty() {
+            return Err(crate::Error::new(NonMonotonicSignerSets { nonces }));
+        }
+
+        Ok(())
+    }
+
+    async fn silent_orchestrators(&self, max_concurrency: usize) -> Result<Vec<SilentOrchestrator>> {
+        if max_concurrency == 0 {
+            bail!("max_concurrency must be non-zero")
+        }
+
+        let keys = self.query_delegate_keys().await?.delegate_keys;
+        let results = stream::iter(keys.into_iter())
+            .map(|keys| async move {
+                let response = self
+                    .query_last_submitted_ethereum_event(&keys.orchestrator_address)
+                    .await?;
+                Ok::<_, crate::Error>((keys, response.event_nonce))
+            })
+            .buffered(max_concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut silent = Vec::new();
+        for result in results {
+            let (keys, event_nonce) = result?;
+            if event_nonce == 0 {
+                silent.push(SilentOrchestrator {
+                    validator_address: keys.validator_address,
+                    orchestrator_address: keys.orchestrator_address,
+                });
+            }
+        }
+
+        Ok(silent)
+    }
+}
+
+/// Rejects the zero nonce, which is never assigned on chain and usually indicates an uninitialized value
+fn validate_nonce(nonce: u64) -> Result<()> {
+    if nonce == 0 {
+        bail!("nonce must be >= 1, got 0")
+    }
+
+    Ok(())
+}
+
+/// Validates a token contract address and returns it in the EIP-55 checksummed form the module stores
+fn normalize_contract(contract: &str) -> Result<String> {
+    Ok(parse_contract(contract)?.to_checksum())
+}
+
+/// Parses a caller supplied token contract, ignoring surrounding whitespace and the `0x` prefix
+fn parse_contract(contract: &str) -> Result<EthAddress> {
+    match contract.trim().parse::<EthAddress>() {
+        Ok(address) => Ok(address),
+        Err(e) => bail!("invalid token contract: {}", e),
+    }
+}
+
+/// Whether a contract stored on chain is `contract`. Stored contracts that don't parse match nothing.
+fn is_contract(contract: &EthAddress, stored: &str) -> bool {
+    stored.parse::<EthAddress>().map_or(false, |stored| stored == *contract)
+}
+
+/// Totals the sends of `contract` in `batches` and in `unbatched`
+fn token_send_totals(
+    contract: &EthAddress,
+    batches: &[BatchTx],
+    unbatched: &[SendToEthereum],
+) -> Result<(SendTotals, SendTotals)> {
+    let mut batched_totals = SendTotals::default();
+    for batch in batches.iter().filter(|batch| is_contract(contract, &batch.token_contract)) {
+        for send in batch.transactions.iter() {
+            batched_totals.add(send)?;
+        }
+    }
+
+    let mut unbatched_totals = SendTotals::default();
+    for send in unbatched {
+        if send.erc20_token.as_ref().map_or(false, |token| is_contract(contract, &token.contract)) {
+            unbatched_totals.add(send)?;
+        }
+    }
+
+    Ok((batched_totals, unbatched_totals))
+}
+
+/// Pages through a paginated query until the node reports no further pages. `fetch` is given the key of the
+/// page to fetch and returns the page's items and the node's `next_key`. Errors once `limits.max_pages` pages
+/// have been fetched or `limits.timeout` elapses without reaching the last page.
+async fn collect_pages<I, F, Fut>(method: &str, limits: PageLimits, fetch: F) -> Result<Vec<I>>
+where
+    F: FnMut(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<(Vec<I>, Vec<u8>)>>,
+{
+    match tokio::time::timeout(limits.timeout, fetch_pages(method, limits.max_pages, fetch)).await {
+        Ok(result) => result,
+        Err(_) => bail!("{} did not reach its last page within {:?}", method, limits.timeout),
+    }
+}
+
+async fn fetch_pages<I, F, Fut>(method: &str, max_pages: usize, mut fetch: F) -> Result<Vec<I>>
+where
+    F: FnMut(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<(Vec<I>, Vec<u8>)>>,
+{
+    let mut items = Vec::new();
+    let mut key = Vec::new();
+    for _ in 0..max_pages {
+        let (page, next_key) = fetch(key).await?;
+        items.extend(page);
+        if next_key.is_empty() {
+            return Ok(items);
+        }
+        key = next_key;
+    }
+
+    bail!(
+        "{} returned more than {} pages, the node may be returning a next_key indefinitely",
+        method,
+        max_pages
+    )
+}
+
+/// Wraps `message` in a request whose gRPC timeout is the time remaining until `deadline`, erroring if the
+/// deadline has already passed
+fn deadline_request<M>(message: M, deadline: Instant) -> Result<tonic::Request<M>> {
+    let remaining = match deadline.checked_duration_since(Instant::now()) {
+        Some(remaining) if !remaining.is_zero() => remaining,
+        _ => bail!("deadline passed before the request was sent"),
+    };
+    let mut request = tonic::Request::new(message);
+    request.set_timeout(remaining);
+
+    Ok(request)
+}
+
+async fn latest_signer_set<T: SommGravityExt + ?Sized>(client: &T) -> Result<SignerSetTx> {
+    match client.query_latest_signer_set_tx().await?.signer_set {
+        Some(signer_set) => Ok(signer_set),
+        None => bail!("latest signer set response did not contain a signer set"),
+    }
+}
+
+async fn contract_denom<T: SommGravityExt + GravityConnection>(client: &T, contract: &str) -> Result<String> {
+    let contract = contract.to_lowercase();
+    let endpoint = client.gravity_endpoint();
+    if let Some(denom) = ERC20_TO_DENOM.get(&endpoint, &contract) {
+        return Ok(denom);
+    }
+
+    let denom = client.query_erc20_to_denom(&contract).await?;
+    if denom.is_empty() {
+        bail!("contract {} is not mapped to a denom", contract)
+    }
+    ERC20_TO_DENOM.insert(&endpoint, &contract, denom.clone());
+
+    Ok(denom)
+}
+
+/// Returns the ERC20 decimals of a contract, or `None` if they cannot be resolved. Only resolved values are
+/// cached, so a transient failure is retried on the next call.
+async fn contract_decimals<T: SommGravityExt + GravityConnection>(client: &T, contract: &str) -> Option<u32> {
+    let contract = contract.to_lowercase();
+    let endpoint = client.gravity_endpoint();
+    if let Some(decimals) = ERC20_DECIMALS.get(&endpoint, &contract) {
+        return Some(decimals);
+    }
+
+    let denom = contract_denom(client, &contract).await.ok()?;
+    let params = client.query_denom_to_erc20_params(&denom).await.ok()?;
+    let decimals = u32::try_from(params.erc20_decimals).ok()?;
+    ERC20_DECIMALS.insert(&endpoint, &contract, decimals);
+
+    Some(decimals)
+}
+
+async fn batch_signer_set<T: SommGravityExt + ?Sized>(client: &T, batch: &BatchTx) -> Result<SignerSetTx> {
+    match signer_set_at_height(client, batch.height).await {
+        Ok(signer_set) => Ok(signer_set),
+        Err(e) => bail!("batch {}: {}", batch.batch_nonce, e),
+    }
+}
+
+/// Calls `attempt` every `poll_interval` until it succeeds, returning the last attempt's error once `timeout` has
+/// elapsed. The final sleep is cut short so the last attempt is made at the deadline.
+async fn poll_until<T, F, Fut>(
+    clock: &dyn Clock,
+    timeout: Duration,
+    poll_interval: Duration,
+    mut attempt: F,
+) -> std::result::Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, String>>,
+{
+    let deadline = clock.now() + timeout;
+    loop {
+        let last = match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(last) => last,
+        };
+
+        let now = clock.now();
+        if now >= deadline {
+            return Err(last);
+        }
+        clock.sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Drives [`SommGravityExt::relay_ready_batches_stream_with_clock`]: polls `relayable_fn` at the height from
+/// `current_height_fn` every `poll_interval`, yielding each batch once until it times out
+fn relay_stream<'a, F, Fut, G, GFut>(
+    poll_interval: Duration,
+    clock: &'a dyn Clock,
+    current_height_fn: F,
+    relayable_fn: G,
+) -> LocalBoxStream<'a, Result<BatchTx>>
+where
+    F: FnMut() -> Fut + 'a,
+    Fut: Future<Output = Result<u64>> + 'a,
+    G: FnMut(u64) -> GFut + 'a,
+    GFut: Future<Output = Result<Vec<BatchTx>>> + 'a,
+{
+    let state = RelayStreamState {
+        current_height_fn,
+        relayable_fn,
+        yielded: BTreeMap::new(),
+        ready: VecDeque::new(),
+        polled: false,
+    };
+
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if let Some(batch) = state.ready.pop_front() {
+                return Some((Ok(batch), state));
+            }
+            if state.polled {
+                clock.sleep(poll_interval).await;
+            }
+            state.polled = true;
+
+            let height = match (state.current_height_fn)().await {
+                Ok(height) => height,
+                Err(e) => return Some((Err(e), state)),
+            };
+            let batches = match (state.relayable_fn)(height).await {
+                Ok(batches) => batches,
+                Err(e) => return Some((Err(e), state)),
+            };
+
+            state.yielded.retain(|_, timeout| *timeout > height);
+            for batch in batches {
+                let key = (batch.token_contract.to_lowercase(), batch.batch_nonce);
+                if state.yielded.insert(key, batch.timeout).is_none() {
+                    state.ready.push_back(batch);
+                }
+            }
+        }
+    })
+    .boxed_local()
+}
+
+/// Returns the gravity module account's bank balance of `denom`
+async fn module_balance<T: SommGravityExt + GravityConnection + ?Sized>(client: &T, denom: &str) -> Result<u128> {
+    let address = client.module_account_address().await?;
+    let mut bank = client.bank_client().await?;
+    let request = QueryBalanceRequest {
+        address: address.clone(),
+        denom: denom.to_string(),
+    };
+    let balance = match bank.inner.balance(request).await {
+        Ok(response) => response.into_inner().balance,
+        Err(e) => bail!("failed to query the {} balance of {}: {}", denom, address, e),
+    };
+
+    match balance {
+        Some(coin) => match coin.amount.parse::<u128>() {
+            Ok(amount) => Ok(amount),
+            Err(e) => bail!("invalid {} balance {} of {}: {}", denom, coin.amount, address, e),
+        },
+        None => Ok(0),
+    }
+}
+
+/// Returns the signer set in effect at `height`, the one with the greatest height at or below it
+async fn signer_set_at_height<T: SommGravityExt + ?Sized>(client: &T, height: u64) -> Result<SignerSetTx> {
+    let signer_set = client
+        .query_all_signer_set_txs()
+        .await?
+        .into_iter()
+        .filter(|s| s.height <= height)
+        .max_by_key(|s| s.height);
+
+    match signer_set {
+        Some(signer_set) => Ok(signer_set),
+        None => bail!("no signer set found at or below height {}", height),
+    }
+}
+
+/// The gRPC header cosmos nodes use to select the height a query is answered at, and report it in responses
+const BLOCK_HEIGHT_HEADER: &str = "x-cosmos-block-height";
+
+/// Fetches a batch's confirmations as of `height`, or the latest height if `None`, returning them with the
+/// height the node reports it answered at
+async fn batch_confirmations_at<T: GravityConnection + ?Sized>(
+    client: &T,
+    token_contract: &str,
+    nonce: u64,
+    height: Option<u64>,
+) -> Result<(Vec<BatchTxConfirmation>, u64)> {
+    let mut client = client.gravity_client().await?;
+    let mut request = tonic::Request::new(BatchTxConfirmationsRequest {
+        token_contract: token_contract.to_string(),
+        batch_nonce: nonce,
+    });
+    if let Some(height) = height {
+        request.metadata_mut().insert(BLOCK_HEIGHT_HEADER, height.into());
+    }
+
+    wire_log::request("batch_tx_confirmations", request.get_ref());
+    let response = client
+        .inner
+        .batch_tx_confirmations(request)
+        .await
+        .map_err(|e| map_status("batch_tx_confirmations", e))?;
+    let answered_at = response
+        .metadata()
+        .get(BLOCK_HEIGHT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let response = response.into_inner();
+    wire_log::response("batch_tx_confirmations", &response);
+
+    match (answered_at, height) {
+        (Some(answered_at), _) => Ok((response.signatures, answered_at)),
+        (None, Some(height)) => Ok((response.signatures, height)),
+        (None, None) => bail!("node did not report the height of its batch_tx_confirmations response"),
+    }
+}
+
+/// Returns the distinct parsed signers of `confirmations`, skipping malformed ones
+fn confirmation_signers(confirmations: &[BatchTxConfirmation]) -> BTreeSet<EthAddress> {
+    confirmations
+        .iter()
+        .filter_map(|c| c.ethereum_signer.parse::<EthAddress>().ok())
+        .collect()
+}
+
+/// Tallies the confirmations that verify against `batch` against `signer_set`
+fn batch_readiness(
+    batch: &BatchTx,
+    gravity_id: &[u8; 32],
+    signer_set: &SignerSetTx,
+    confirmations: &[BatchTxConfirmation],
+) -> Result<Readiness> {
+    let mut confirmed = Vec::new();
+    for confirmation in confirmations {
+        if verify_batch_confirmation(batch, gravity_id, confirmation)? {
+            confirmed.push(confirmation.ethereum_signer.parse::<EthAddress>()?);
+        }
+    }
+
+    Ok(Readiness::new(signer_set, &confirmed))
+}
+
+/// Polling state of [`SommGravityExt::relay_ready_batches_stream`]
+struct RelayStreamState<F, G> {
+    current_height_fn: F,
+    relayable_fn: G,
+    /// Timeouts of the batches already yielded, keyed by lowercased token contract and nonce
+    yielded: BTreeMap<(String, u64), u64>,
+    ready: VecDeque<BatchTx>,
+    polled: bool,
+}
+
+/// A signer set together with the confirmations submitted for it
+#[derive(Clone, Debug)]
+pub struct SignerSetConfirmations {
+    pub signer_set: SignerSetTx,
+    pub confirmations: Vec<SignerSetTxConfirmation>,
+}
+
+/// Cosmos side totals for one ERC20 token, as computed by [`SommGravityExt::reconcile_token`]
+#[derive(Clone, Debug, Default)]
+pub struct TokenReconciliation {
+    pub token_contract: String,
+    /// The cosmos denom the contract maps to, from `query_erc20_to_denom`
+    pub denom: String,
+    /// Sends already included in an outgoing batch, from `query_batch_txs`
+    pub batched: SendTotals,
+    /// Sends still waiting in the unbatched pool, from `query_unbatched_send_to_ethereums` for the given senders
+    pub unbatched: SendTotals,
+    /// The gravity module account's bank balance of `denom`. It escrows the amount and fee of every send until
+    /// it executes on Ethereum, and for cosmos originated tokens also holds everything bridged out.
+    pub reserve: u128,
+}
+
+/// Summed amounts and bridge fees over a set of sends of a single token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SendTotals {
+    pub count: u64,
+    pub amount: u128,
+    pub fees: u128,
+}
+
+impl SendTotals {
+    fn add(&mut self, send: &SendToEthereum) -> Result<()> {
+        let amount = match send.erc20_token.as_ref() {
+            Some(token) => parse_erc20_amount(token)?,
+            None => bail!("send {} has no erc20 token", send.id),
+        };
+        let fee = match send.erc20_fee.as_ref() {
+            Some(fee) => parse_erc20_amount(fee)?,
+            None => 0,
+        };
+
+        self.count += 1;
+        self.amount = match self.amount.checked_add(amount) {
+            Some(total) => total,
+            None => bail!("amount total overflowed at send {}", send.id),
+        };
+        self.fees = match self.fees.checked_add(fee) {
+            Some(total) => total,
+            None => bail!("fee total overflowed at send {}", send.id),
+        };
+
+        Ok(())
+    }
+}
+
+/// Per validator confirmation progress, as computed by [`SommGravityExt::validator_confirmation_status`]
+#[derive(Clone, Debug)]
+pub struct ConfirmationStatus {
+    pub batches: ConfirmationCounts<BatchTx>,
+    pub signer_sets: ConfirmationCounts<SignerSetTx>,
+    pub contract_calls: ConfirmationCounts<ContractCallTx>,
+}
+
+/// How many of one kind of outgoing tx a validator has confirmed, and the ones it has not
+#[derive(Clone, Debug)]
+pub struct ConfirmationCounts<T> {
+    /// Number of txs of this kind currently stored on chain
+    pub total: usize,
+    /// Number of stored txs the validator is not required to confirm or has already confirmed
+    pub confirmed: usize,
+    /// Txs the validator still needs to confirm
+    pub outstanding: Vec<T>,
+}
+
+impl<T> ConfirmationCounts<T> {
+    fn new(total: usize, outstanding: Vec<T>) -> Self {
+        Self {
+            total,
+            confirmed: total.saturating_sub(outstanding.len()),
+            outstanding,
+        }
+    }
+}
+
+/// A send with its amounts rendered for display, as returned by [`SommGravityExt::display_unbatched_sends`]
+#[derive(Clone, Debug)]
+pub struct SendDisplay {
+    pub id: u64,
+    pub sender: String,
+    pub ethereum_recipient: String,
+    pub token_contract: String,
+    /// The amount as a decimal string, or the raw integer if the token's decimals are unknown
+    pub amount: String,
+    /// The bridge fee as a decimal string, or the raw integer if the token's decimals are unknown
+    pub bridge_fee: String,
+}
+
+/// A lightweight summary of bridge state, as returned by [`SommGravityExt::query_overview`]. A field is `None`
+/// if its query failed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Overview {
+    pub latest_signer_set_nonce: Option<u64>,
+    pub params_bridge_active: Option<bool>,
+    pub total_batches: Option<u64>,
+    pub total_contract_calls: Option<u64>,
+}
+
+/// Typed ERC20 parameters for a cosmos denom, as returned by [`SommGravityExt::query_denom_erc20_params`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DenomErc20Params {
+    pub base_denom: String,
+    pub erc20_decimals: u32,
+    pub erc20_name: String,
+    pub erc20_symbol: String,
+    /// The deployed ERC20 contract, if the token has been deployed
+    pub erc20_contract: Option<EthAddress>,
+}
+
+impl TryFrom<DenomToErc20ParamsResponse> for DenomErc20Params {
+    type Error = crate::Error;
+
+    /// Requires a non-empty base denom, name and symbol, and decimals that fit the ERC20 `uint8`
+    fn try_from(response: DenomToErc20ParamsResponse) -> Result<Self> {
+        if response.base_denom.is_empty() {
+            bail!("denom to erc20 params response has an empty base denom")
+        }
+        if response.erc20_name.is_empty() || response.erc20_symbol.is_empty() {
+            bail!(
+                "denom to erc20 params response for {} has an empty name or symbol",
+                response.base_denom
+            )
+        }
+        if response.erc20_decimals > u8::MAX as u64 {
+            bail!(
+                "denom to erc20 params response for {} has invalid decimals {}",
+                response.base_denom,
+                response.erc20_decimals
+            )
+        }
+
+        Ok(Self {
+            base_denom: response.base_denom,
+            erc20_decimals: response.erc20_decimals as u32,
+            erc20_name: response.erc20_name,
+            erc20_symbol: response.erc20_symbol,
+            erc20_contract: None,
+        })
+    }
+}
+
+/// The outgoing txs an orchestrator has yet to sign, as returned by [`SommGravityExt::query_pending_work`]
+#[derive(Clone, Debug, Default)]
+pub struct PendingWork {
+    pub signer_sets: Vec<SignerSetTx>,
+    pub batches: Vec<BatchTx>,
+    pub contract_calls: Vec<ContractCallTx>,
+}
+
+impl PendingWork {
+    pub fn is_empty(&self) -> bool {
+        self.signer_sets.is_empty() && self.batches.is_empty() && self.contract_calls.is_empty()
+    }
+}
+
+/// A sender's transfers split by whether they have been batched, as returned by
+/// [`SommGravityExt::classify_sends`]
+#[derive(Clone, Debug, Default)]
+pub struct SendClassification {
+    pub batched: Vec<SendToEthereum>,
+    pub unbatched: Vec<SendToEthereum>,
+}
+
+/// The confirmations of one batch, as returned by [`SommGravityExt::query_batch_tx_confirmations_many`]
+#[derive(Clone, Debug)]
+pub struct BatchConfirmations {
+    pub token_contract: String,
+    pub batch_nonce: u64,
+    pub confirmations: Vec<BatchTxConfirmation>,
+}
+
+/// A batch whose confirmations could not be fetched
+#[derive(Clone, Debug)]
+pub struct BatchConfirmationsFailure {
+    pub token_contract: String,
+    pub batch_nonce: u64,
+    pub error: String,
+}
+
+/// The result of fetching many batches' confirmations, as returned by
+/// [`SommGravityExt::query_batch_tx_confirmations_many`]. Both lists keep the order the batches were requested
+/// in.
+#[derive(Clone, Debug, Default)]
+pub struct BatchConfirmationsMany {
+    pub confirmations: Vec<BatchConfirmations>,
+    /// Batches whose query failed. Always empty unless `partial_ok` was set.
+    pub failed: Vec<BatchConfirmationsFailure>,
+}
+
+impl BatchConfirmationsMany {
+    /// Returns whether every batch's confirmations were fetched
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// An orchestrator that has never submitted an Ethereum event, as returned by
+/// [`SommGravityExt::silent_orchestrators`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SilentOrchestrator {
+    pub validator_address: String,
+    pub orchestrator_address: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+    use crate::clock::MockClock;
+
+    const USDC: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn send(id: u64, contract: &str, amount: u128, fee: u128) -> SendToEthereum {
+        SendToEthereum {
+            id,
+            erc20_token: Some(Erc20Token {
+                contract: contract.to_string(),
+                amount: amount.to_string(),
+            }),
+            erc20_fee: Some(Erc20Token {
+                contract: contract.to_string(),
+                amount: fee.to_string(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn batch(contract: &str, transactions: Vec<SendToEthereum>) -> BatchTx {
+        BatchTx {
+            token_contract: contract.to_string(),
+            transactions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn token_send_totals_accepts_unprefixed_contract() {
+        let contract = parse_contract(&format!("  {}\n", USDC.trim_start_matches("0x").to_uppercase())).unwrap();
+        let batches = vec![
+            batch(USDC, vec![send(1, USDC, 100, 1), send(2, USDC, 200, 2)]),
+            batch(WETH, vec![send(3, WETH, 1_000, 10)]),
+        ];
+        let unbatched = vec![send(4, &USDC.to_uppercase().replace("0X", "0x"), 50, 5), send(5, WETH, 500, 50)];
+
+        let (batched, unbatched) = token_send_totals(&contract, &batches, &unbatched).unwrap();
+        assert_eq!(
+            batched,
+            SendTotals {
+                count: 2,
+                amount: 300,
+                fees: 3
+            }
+        );
+        assert_eq!(
+            unbatched,
+            SendTotals {
+                count: 1,
+                amount: 50,
+                fees: 5
+            }
+        );
+    }
+
+    #[test]
+    fn token_send_totals_skips_unparseable_stored_contracts() {
+        let contract = parse_contract(USDC).unwrap();
+        let batches = vec![batch("not a contract", vec![send(1, USDC, 100, 1)])];
+
+        let (batched, unbatched) = token_send_totals(&contract, &batches, &[]).unwrap();
+        assert_eq!(batched, SendTotals::default());
+        assert_eq!(unbatched, SendTotals::default());
+    }
+
+    #[test]
+    fn parse_contract_rejects_invalid_input() {
+        assert!(parse_contract("0x1234").is_err());
+        assert!(parse_contract("").is_err());
+    }
+
+    /// A connection whose every query fails, for driving the polling helpers without a node
+    struct Unreachable;
+
+    #[async_trait(?Send)]
+    impl GravityConnection for Unreachable {
+        async fn gravity_client(&self) -> Result<SommGravityQueryClient> {
+            bail!("unreachable")
+        }
+
+        fn gravity_endpoint(&self) -> String {
+            "http://unreachable:9090".to_string()
+        }
+    }
+
+    #[tokio::test]
+    async fn await_delegate_keys_times_out_at_deadline() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let err = Unreachable
+            .await_delegate_keys_with_clock(
+                "sommvaloper1",
+                USDC,
+                "somm1",
+                Duration::from_secs(10),
+                Duration::from_secs(3),
+                &clock,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert!(err.to_string().contains("unreachable"), "{}", err);
+        assert_eq!(clock.now() - start, Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn poll_until_succeeds_on_final_clamped_poll() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let attempts = RefCell::new(Vec::new());
+        let result = poll_until(&clock, Duration::from_secs(10), Duration::from_secs(3), || {
+            let elapsed = clock.now() - start;
+            attempts.borrow_mut().push(elapsed);
+            async move {
+                if elapsed >= Duration::from_secs(10) {
+                    Ok(elapsed)
+                } else {
+                    Err("not yet".to_string())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(Duration::from_secs(10)));
+        assert_eq!(
+            attempts.into_inner(),
+            [0, 3, 6, 9, 10].map(Duration::from_secs).to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn poll_until_returns_last_error_on_timeout() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let attempts = Cell::new(0);
+        let result: std::result::Result<(), String> =
+            poll_until(&clock, Duration::from_secs(5), Duration::from_secs(2), || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move { Err(format!("attempt {}", attempt)) }
+            })
+            .await;
+
+        // Attempts at 0, 2, 4 and the clamped one at 5
+        assert_eq!(result, Err("attempt 4".to_string()));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn relay_stream_polls_every_interval() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        let polls = RefCell::new(Vec::new());
+        let stream = relay_stream(
+            Duration::from_secs(5),
+            &clock,
+            || async { Ok(100) },
+            |_height| {
+                polls.borrow_mut().push(clock.now() - start);
+                let nonce = polls.borrow().len() as u64;
+                async move {
+                    Ok(vec![BatchTx {
+                        batch_nonce: nonce,
+                        timeout: 1_000,
+                        token_contract: USDC.to_string(),
+                        ..Default::default()
+                    }])
+                }
+            },
+        );
+
+        let nonces = stream
+            .take(3)
+            .map(|batch| batch.unwrap().batch_nonce)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(nonces, vec![1, 2, 3]);
+        assert_eq!(polls.into_inner(), [0, 5, 10].map(Duration::from_secs).to_vec());
     }
 }
@@ -0,0 +1,61 @@
+//! Pairs a contract call's invalidation scope with its nonce
+use std::{fmt, str::FromStr};
+
+use crate::{bail, Error, Result};
+
+/// Identifies a contract call tx. The scope and nonce always travel together so they cannot be swapped or
+/// mismatched between calls. The string form is the hex scope and decimal nonce separated by a colon, for
+/// example `0xdeadbeef:7`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Invalidation {
+    pub scope: Vec<u8>,
+    pub nonce: u64,
+}
+
+impl Invalidation {
+    /// Creates an invalidation, rejecting an empty scope
+    pub fn new(scope: Vec<u8>, nonce: u64) -> Result<Self> {
+        if scope.is_empty() {
+            bail!("invalidation scope must not be empty")
+        }
+
+        Ok(Self { scope, nonce })
+    }
+
+    /// Creates an invalidation from a hex encoded scope, with or without a `0x` prefix
+    pub fn from_hex(scope: &str, nonce: u64) -> Result<Self> {
+        let stripped = scope.strip_prefix("0x").unwrap_or(scope);
+        match hex::decode(stripped) {
+            Ok(scope) => Self::new(scope, nonce),
+            Err(e) => bail!("invalid invalidation scope {}: {}", scope, e),
+        }
+    }
+
+    /// Returns the scope as `0x` prefixed hex
+    pub fn scope_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.scope))
+    }
+}
+
+impl FromStr for Invalidation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (scope, nonce) = match s.rsplit_once(':') {
+            Some(parts) => parts,
+            None => bail!("invalid invalidation {}: expected <scope hex>:<nonce>", s),
+        };
+        let nonce = match nonce.parse::<u64>() {
+            Ok(nonce) => nonce,
+            Err(e) => bail!("invalid invalidation nonce {}: {}", nonce, e),
+        };
+
+        Self::from_hex(scope, nonce)
+    }
+}
+
+impl fmt::Display for Invalidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.scope_hex(), self.nonce)
+    }
+}
@@ -1,3 +1,91 @@
+//! Sommelier gravity module support for `ocular`.
+//!
+//! With default features this crate provides the [`SommGravityExt`] query extension for `ocular`'s
+//! `GrpcClient` alongside the [`SommGravity`] message enum. Tools that only encode messages for offline
+//! signing can depend on it with `default-features = false`, which drops the query client and this crate's own
+//! `tonic`, `async-trait`, `futures` and `tokio` dependencies while keeping message encoding, signing and
+//! checkpoint helpers.
+//!
+//! This does not remove `tonic` from the dependency tree. Message encoding needs `ocular`'s `ModuleMsg` and
+//! `gravity_proto`'s message types, and both of those crates depend on `tonic` and its transport
+//! unconditionally. Turning off default features only saves compiling this crate's query code.
+//!
+//! Errors are [`eyre::Report`]s by default. Enabling the `anyhow` feature switches [`Error`] and [`Result`] to
+//! `anyhow`'s types instead. `eyre` remains in the dependency tree either way since `ocular` uses it. Errors
+//! returned by `ocular` and `cosmrs` are `eyre::Report`s, which don't convert into `anyhow::Error` with `?`,
+//! so they are matched and re-raised with `bail!` wherever the crate calls them.
+#[cfg(not(feature = "anyhow"))]
+pub use eyre::{Report as Error, Result};
+#[cfg(not(feature = "anyhow"))]
+pub(crate) use eyre::bail;
+
+#[cfg(feature = "anyhow")]
+pub use anyhow::{Error, Result};
+#[cfg(feature = "anyhow")]
+pub(crate) use anyhow::bail;
+
+pub mod address;
+pub mod amounts;
+#[cfg(feature = "query")]
+mod cache;
+pub mod checkpoint;
+#[cfg(feature = "query")]
+pub mod clock;
+pub mod confirmations;
+#[cfg(feature = "query")]
+pub mod connections;
+pub mod display;
+#[cfg(feature = "query")]
+pub mod endpoint;
+#[cfg(feature = "query")]
+pub mod error;
+#[cfg(feature = "query")]
 pub mod extension;
+pub mod invalidation;
+pub mod msgs;
+pub mod observer;
+pub mod pagination;
+#[cfg(feature = "ethers")]
+pub mod preflight;
+#[cfg(feature = "query")]
+pub mod session;
+pub mod signature;
+#[cfg(feature = "query")]
+pub mod state_cache;
+#[cfg(feature = "query")]
+pub mod submit;
+#[cfg(feature = "query")]
+mod wire_log;
+#[cfg(feature = "serde")]
+pub mod snapshot;
 
+pub use crate::address::*;
+pub use crate::amounts::*;
+pub use crate::checkpoint::*;
+#[cfg(feature = "query")]
+pub use crate::clock::*;
+pub use crate::confirmations::*;
+#[cfg(feature = "query")]
+pub use crate::connections::*;
+pub use crate::display::*;
+#[cfg(feature = "query")]
+pub use crate::endpoint::*;
+#[cfg(feature = "query")]
+pub use crate::error::*;
+#[cfg(feature = "query")]
 pub use crate::extension::*;
+pub use crate::invalidation::*;
+pub use crate::msgs::*;
+pub use crate::observer::*;
+pub use crate::pagination::*;
+#[cfg(feature = "ethers")]
+pub use crate::preflight::*;
+#[cfg(feature = "query")]
+pub use crate::session::*;
+pub use crate::signature::*;
+#[cfg(feature = "query")]
+pub use crate::state_cache::*;
+#[cfg(feature = "query")]
+pub use crate::submit::*;
+#[cfg(feature = "serde")]
+pub use crate::snapshot::*;
@@ -0,0 +1,921 @@
+//! Defines the message enum for Sommelier's gravity module. Nothing in this module depends on the query
+//! client, so it remains available when the `query` feature is disabled.
+//!
+//! With the `serde` feature, [`SommGravity`] serializes to JSON so messages can be persisted, for example in an
+//! on disk queue of pending submissions, and reloaded. Byte fields are `0x` prefixed hex, `Coin`s are
+//! `{"denom", "amount"}` and `Any`s are `{"type_url", "value"}`. String fields borrow from the input, so
+//! deserialize with `serde_json::from_str` or `from_slice` from a buffer that outlives the message. A borrowed
+//! field can't hold a JSON string containing an escape sequence such as `\u0073`, since the unescaped value
+//! isn't in the buffer, so such input fails to deserialize. `serde_json` never escapes the addresses and denoms
+//! these fields hold, so this only affects JSON written by other encoders.
+use ocular::{
+    cosmrs::{AccountId, Coin, Denom},
+    tx::{ModuleMsg, UnsignedTx},
+};
+use prost_types::Any;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    address::EthAddress,
+    amounts::validate_denom,
+    bail,
+    invalidation::Invalidation,
+    signature::verify_delegate_keys_signature,
+    Result,
+};
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SommGravity<'m> {
+    /// Represents a MsgSendToEthereum
+    SendToEthereum {
+        sender: &'m str,
+        ethereum_recipient: &'m str,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::coin"))]
+        amount: Coin,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::coin"))]
+        bridge_fee: Coin,
+    },
+    /// Represents a MsgCancelSendToEthereum
+    CancelSendToEthereum { sender: &'m str, id: u64 },
+    /// Represents a MsgRequestBatchTx. A requested batch can't be cancelled; see
+    /// [`SommGravityExt::batch_timeout`](crate::extension::SommGravityExt::batch_timeout) for how a stuck
+    /// batch is released.
+    RequestBatchTx { denom: &'m str, signer: &'m str },
+    /// Represents a MsgSubmitEthereumTxConfirmation
+    SubmitEthereumTxConfirmation {
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::any"))]
+        confirmation: Any,
+        signer: &'m str,
+    },
+    /// Represent a ContractCallTxConfirmation
+    ContractCallTxConfirmation {
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::hex_bytes"))]
+        invalidation_scope: Vec<u8>,
+        invalidation_nonce: u64,
+        ethereum_signer: &'m str,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::hex_bytes"))]
+        signature: Vec<u8>,
+    },
+    /// Represents a BatchTxConfirmation
+    BatchTxConfirmation {
+        token_contract_address: &'m str,
+        batch_nonce: u64,
+        ethereum_signer: &'m str,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::hex_bytes"))]
+        signature: Vec<u8>,
+    },
+    /// Represents a SignerSetTxConfirmation
+    SignerSetTxConfirmation {
+        signer_set_nonce: u64,
+        ethereum_signer: &'m str,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::hex_bytes"))]
+        signature: Vec<u8>,
+    },
+    /// Represents a MsgSubmitEthereumEvent
+    SubmitEthereumEvent {
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::any"))]
+        event: Any,
+        signer: &'m str,
+    },
+    /// Represents a MsgSetDelegateKeys. Build it with [`SommGravity::set_delegate_keys`] to check that
+    /// `eth_signature` was made by `ethereum_address`. Constructing the variant directly skips that check, for
+    /// flows where the signed nonce isn't known locally; the chain still rejects a mismatched signature.
+    SetDelegateKeys {
+        validator_address: &'m str,
+        orchestrator_address: &'m str,
+        ethereum_address: &'m str,
+        #[cfg_attr(feature = "serde", serde(with = "crate::snapshot::hex_bytes"))]
+        eth_signature: Vec<u8>,
+    },
+    /// Represents a DelegateKeysMsg
+    DelegateKeysSignMsg {
+        validator_address: &'m str,
+        nonce: u64,
+    },
+    /// Represents a MsgSubmitEthereumHeightVote
+    SubmitEthereumHeightVote {
+        ethereum_height: u64,
+        signer: &'m str,
+    },
+}
+
+impl<'m> SommGravity<'m> {
+    /// Builds a [`SommGravity::SendToEthereum`] from raw integer amounts and denoms rather than [`Coin`]s.
+    /// The denoms must parse and match, and `amount` must be non-zero.
+    pub fn send_to_ethereum(
+        sender: &'m str,
+        ethereum_recipient: &'m str,
+        amount: u128,
+        denom: &str,
+        fee_amount: u128,
+        fee_denom: &str,
+    ) -> Result<Self> {
+        let msg = SommGravity::SendToEthereum {
+            sender,
+            ethereum_recipient,
+            amount: Coin {
+                denom: parse_denom(denom)?,
+                amount,
+            },
+            bridge_fee: Coin {
+                denom: parse_denom(fee_denom)?,
+                amount: fee_amount,
+            },
+        };
+        msg.validate()?;
+
+        Ok(msg)
+    }
+
+    /// Builds a [`SommGravity::ContractCallTxConfirmation`] from an [`Invalidation`]
+    pub fn contract_call_tx_confirmation(
+        invalidation: Invalidation,
+        ethereum_signer: &'m str,
+        signature: Vec<u8>,
+    ) -> Self {
+        SommGravity::ContractCallTxConfirmation {
+            invalidation_scope: invalidation.scope,
+            invalidation_nonce: invalidation.nonce,
+            ethereum_signer,
+            signature,
+        }
+    }
+
+    /// Builds a [`SommGravity::SetDelegateKeys`], checking that `eth_signature` recovers to `ethereum_address`
+    /// over the `DelegateKeysSignMsg` for `validator_address` and `nonce`, the validator account's sequence
+    /// the signature was made for. The chain rejects a mismatched signature and address, so this only surfaces
+    /// that rejection before broadcasting.
+    pub fn set_delegate_keys(
+        validator_address: &'m str,
+        orchestrator_address: &'m str,
+        ethereum_address: &'m str,
+        eth_signature: Vec<u8>,
+        nonce: u64,
+    ) -> Result<Self> {
+        let msg = SommGravity::SetDelegateKeys {
+            validator_address,
+            orchestrator_address,
+            ethereum_address,
+            eth_signature,
+        };
+        msg.validate()?;
+        msg.verify_delegate_keys_signature(nonce)?;
+
+        Ok(msg)
+    }
+
+    /// Errors unless the message is a [`SommGravity::SetDelegateKeys`] whose `eth_signature` recovers to its
+    /// `ethereum_address` for `nonce`. See [`SommGravity::set_delegate_keys`].
+    pub fn verify_delegate_keys_signature(&self, nonce: u64) -> Result<()> {
+        match self {
+            SommGravity::SetDelegateKeys {
+                validator_address,
+                ethereum_address,
+                eth_signature,
+                ..
+            } => {
+                if !verify_delegate_keys_signature(validator_address, ethereum_address, nonce, eth_signature) {
+                    bail!(
+                        "eth_signature was not made by {} for validator {} at nonce {}",
+                        ethereum_address,
+                        validator_address,
+                        nonce
+                    )
+                }
+
+                Ok(())
+            }
+            _ => bail!("not a SetDelegateKeys message"),
+        }
+    }
+
+    /// Runs local, non-network checks on the message without encoding it. Per variant:
+    ///
+    /// * `SendToEthereum`: `sender` is bech32, `ethereum_recipient` is a 20 byte hex address, `amount` is
+    ///   non-zero in a denom accepted by [`validate_denom`] and `bridge_fee` has the same denom as `amount`
+    /// * `CancelSendToEthereum`: `sender` is bech32 and `id` is non-zero
+    /// * `RequestBatchTx`: `denom` is accepted by [`validate_denom`] and `signer` is bech32
+    /// * `SubmitEthereumTxConfirmation`: `confirmation` has a type_url and `signer` is bech32
+    /// * `ContractCallTxConfirmation`: `invalidation_scope` is non-empty, `ethereum_signer` is a 20 byte hex
+    ///   address and `signature` is 65 bytes
+    /// * `BatchTxConfirmation`: `token_contract_address` and `ethereum_signer` are 20 byte hex addresses,
+    ///   `batch_nonce` is non-zero and `signature` is 65 bytes
+    /// * `SignerSetTxConfirmation`: `signer_set_nonce` is non-zero, `ethereum_signer` is a 20 byte hex address
+    ///   and `signature` is 65 bytes
+    /// * `SubmitEthereumEvent`: `event` has a type_url and `signer` is bech32
+    /// * `SetDelegateKeys`: `validator_address` and `orchestrator_address` are bech32, `ethereum_address` is a
+    ///   20 byte hex address and `eth_signature` is 65 bytes. Checking that the signature recovers to
+    ///   `ethereum_address` needs the signed nonce; see [`SommGravity::set_delegate_keys`]
+    /// * `DelegateKeysSignMsg`: `validator_address` is bech32
+    /// * `SubmitEthereumHeightVote`: `signer` is bech32 and `ethereum_height` is non-zero
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SommGravity::SendToEthereum {
+                sender,
+                ethereum_recipient,
+                amount,
+                bridge_fee,
+            } => {
+                validate_cosmos_address("sender", sender)?;
+                validate_ethereum_address("ethereum_recipient", ethereum_recipient)?;
+                validate_denom(amount.denom.as_ref())?;
+                if amount.amount == 0 {
+                    bail!("amount must be non-zero")
+                }
+                if bridge_fee.denom != amount.denom {
+                    bail!(
+                        "bridge_fee denom {} does not match amount denom {}",
+                        bridge_fee.denom,
+                        amount.denom
+                    )
+                }
+            }
+            SommGravity::CancelSendToEthereum { sender, id } => {
+                validate_cosmos_address("sender", sender)?;
+                if *id == 0 {
+                    bail!("id must be non-zero")
+                }
+            }
+            SommGravity::RequestBatchTx { denom, signer } => {
+                validate_denom(denom)?;
+                validate_cosmos_address("signer", signer)?;
+            }
+            SommGravity::SubmitEthereumTxConfirmation {
+                confirmation,
+                signer,
+            } => {
+                if confirmation.type_url.is_empty() {
+                    bail!("confirmation type_url must not be empty")
+                }
+                validate_cosmos_address("signer", signer)?;
+            }
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope,
+                invalidation_nonce: _,
+                ethereum_signer,
+                signature,
+            } => {
+                if invalidation_scope.is_empty() {
+                    bail!("invalidation_scope must not be empty")
+                }
+                validate_ethereum_address("ethereum_signer", ethereum_signer)?;
+                validate_signature("signature", signature)?;
+            }
+            SommGravity::BatchTxConfirmation {
+                token_contract_address,
+                batch_nonce,
+                ethereum_signer,
+                signature,
+            } => {
+                validate_ethereum_address("token_contract_address", token_contract_address)?;
+                if *batch_nonce == 0 {
+                    bail!("batch_nonce must be non-zero")
+                }
+                validate_ethereum_address("ethereum_signer", ethereum_signer)?;
+                validate_signature("signature", signature)?;
+            }
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce,
+                ethereum_signer,
+                signature,
+            } => {
+                if *signer_set_nonce == 0 {
+                    bail!("signer_set_nonce must be non-zero")
+                }
+                validate_ethereum_address("ethereum_signer", ethereum_signer)?;
+                validate_signature("signature", signature)?;
+            }
+            SommGravity::SubmitEthereumEvent { event, signer } => {
+                if event.type_url.is_empty() {
+                    bail!("event type_url must not be empty")
+                }
+                validate_cosmos_address("signer", signer)?;
+            }
+            SommGravity::SetDelegateKeys {
+                validator_address,
+                orchestrator_address,
+                ethereum_address,
+                eth_signature,
+            } => {
+                validate_cosmos_address("validator_address", validator_address)?;
+                validate_cosmos_address("orchestrator_address", orchestrator_address)?;
+                validate_ethereum_address("ethereum_address", ethereum_address)?;
+                validate_signature("eth_signature", eth_signature)?;
+            }
+            SommGravity::DelegateKeysSignMsg {
+                validator_address,
+                nonce: _,
+            } => {
+                validate_cosmos_address("validator_address", validator_address)?;
+            }
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height,
+                signer,
+            } => {
+                validate_cosmos_address("signer", signer)?;
+                if *ethereum_height == 0 {
+                    bail!("ethereum_height must be non-zero")
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_denom(denom: &str) -> Result<Denom> {
+    validate_denom(denom)?;
+    match denom.parse::<Denom>() {
+        Ok(denom) => Ok(denom),
+        Err(e) => bail!("invalid denom {}: {}", denom, e),
+    }
+}
+
+fn validate_cosmos_address(field: &str, address: &str) -> Result<()> {
+    if let Err(e) = address.parse::<AccountId>() {
+        bail!("invalid {} {}: {}", field, address, e)
+    }
+
+    Ok(())
+}
+
+fn validate_ethereum_address(field: &str, address: &str) -> Result<()> {
+    if let Err(e) = address.parse::<EthAddress>() {
+        bail!("invalid {}: {}", field, e)
+    }
+
+    Ok(())
+}
+
+fn validate_signature(field: &str, signature: &[u8]) -> Result<()> {
+    if signature.len() != 65 {
+        bail!("{} must be 65 bytes, got {}", field, signature.len())
+    }
+
+    Ok(())
+}
+
+impl ModuleMsg for SommGravity<'_> {
+    type Error = crate::Error;
+
+    /// Converts the enum into an [`Any`] for use in a transaction. The message is checked with
+    /// [`SommGravity::validate`] before encoding, so messages the chain would reject, such as a zero nonce or an
+    /// address that doesn't parse, now fail here where earlier versions encoded them without complaint. Type
+    /// URLs use the default `gravity.v1` package; see [`MsgContext`] to target another.
+    fn into_any(self) -> Result<Any> {
+        self.into_any_in(&MsgContext::default())
+    }
+
+    /// Converts the message enum representation into an [`UnsignedTx`] containing the corresponding Msg
+    fn into_tx(self) -> Result<UnsignedTx> {
+        self.into_tx_in(&MsgContext::default())
+    }
+}
+
+impl SommGravity<'_> {
+    fn into_any_in(self, context: &MsgContext) -> Result<Any> {
+        self.validate()?;
+
+        match self {
+            SommGravity::SendToEthereum {
+                sender,
+                ethereum_recipient,
+                amount,
+                bridge_fee,
+            } => {
+                let msg = gravity_proto::gravity::MsgSendToEthereum {
+                    sender: sender.to_string(),
+                    ethereum_recipient: ethereum_recipient.to_string(),
+                    amount: Some(amount.into()),
+                    bridge_fee: Some(bridge_fee.into()),
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgSendToEthereum: {}", e)
+                };
+                any.type_url = context.type_url("MsgSendToEthereum");
+                Ok(any)
+            },
+            SommGravity::CancelSendToEthereum { sender, id } => {
+                let msg = gravity_proto::gravity::MsgCancelSendToEthereum {
+                    sender: sender.to_string(),
+                    id,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgCancelSendToEthereum: {}", e)
+                };
+                any.type_url = context.type_url("MsgCancelSendToEthereum");
+                Ok(any)
+            },
+            SommGravity::RequestBatchTx { denom, signer } => {
+                let msg = gravity_proto::gravity::MsgRequestBatchTx {
+                    denom: denom.to_string(),
+                    signer: signer.to_string(),
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgRequestBatchTx: {}", e)
+                };
+                any.type_url = context.type_url("MsgRequestBatchTx");
+                Ok(any)
+            },
+            SommGravity::SubmitEthereumTxConfirmation {
+                confirmation,
+                signer,
+            } => {
+                let msg = gravity_proto::gravity::MsgSubmitEthereumTxConfirmation {
+                    confirmation: Some(confirmation),
+                    signer: signer.to_string(),
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgSubmitEthereumTxConfirmation: {}", e)
+                };
+                any.type_url = context.type_url("MsgSubmitEthereumTxConfirmation");
+                Ok(any)
+            },
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope,
+                invalidation_nonce,
+                ethereum_signer,
+                signature,
+            } => {
+                let msg = gravity_proto::gravity::ContractCallTxConfirmation {
+                    invalidation_scope,
+                    invalidation_nonce,
+                    ethereum_signer: ethereum_signer.to_string(),
+                    signature,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode ContractCallTxConfirmation: {}", e)
+                };
+                any.type_url = context.type_url("ContractCallTxConfirmation");
+                Ok(any)
+            },
+            SommGravity::BatchTxConfirmation {
+                token_contract_address,
+                batch_nonce,
+                ethereum_signer,
+                signature,
+            } => {
+                let msg = gravity_proto::gravity::BatchTxConfirmation {
+                    token_contract: token_contract_address.to_string(),
+                    batch_nonce,
+                    ethereum_signer: ethereum_signer.to_string(),
+                    signature,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode BatchTxConfirmation: {}", e)
+                };
+                any.type_url = context.type_url("BatchTxConfirmation");
+                Ok(any)
+            },
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce,
+                ethereum_signer,
+                signature,
+            } => {
+                let msg = gravity_proto::gravity::SignerSetTxConfirmation {
+                    signer_set_nonce,
+                    ethereum_signer: ethereum_signer.to_string(),
+                    signature,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode SignerSetTxConfirmation: {}", e)
+                };
+                any.type_url = context.type_url("SignerSetTxConfirmation");
+                Ok(any)
+            },
+            SommGravity::SubmitEthereumEvent { event, signer } => {
+                let msg = gravity_proto::gravity::MsgSubmitEthereumEvent {
+                    event: Some(event),
+                    signer: signer.to_string(),
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgSubmitEthereumEvent: {}", e)
+                };
+                any.type_url = context.type_url("MsgSubmitEthereumEvent");
+                Ok(any)
+            },
+            SommGravity::SetDelegateKeys { validator_address, orchestrator_address, ethereum_address, eth_signature } => {
+                let msg = gravity_proto::gravity::MsgDelegateKeys {
+                    validator_address: validator_address.to_string(),
+                    orchestrator_address: orchestrator_address.to_string(),
+                    ethereum_address: ethereum_address.to_string(),
+                    eth_signature,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgDelegateKeys: {}", e)
+                };
+                any.type_url = context.type_url("MsgDelegateKeys");
+                Ok(any)
+            },
+            SommGravity::DelegateKeysSignMsg { validator_address, nonce } => {
+                let msg = gravity_proto::gravity::DelegateKeysSignMsg {
+                    validator_address: validator_address.to_string(),
+                    nonce,
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode DelegateKeysSignMsg: {}", e)
+                };
+                any.type_url = context.type_url("DelegateKeysSignMsg");
+                Ok(any)
+            },
+            SommGravity::SubmitEthereumHeightVote { ethereum_height, signer } => {
+                let msg = gravity_proto::gravity::MsgEthereumHeightVote {
+                    ethereum_height,
+                    signer: signer.to_string(),
+                };
+                let mut any = Any::default();
+                if let Err(e) = prost::Message::encode(&msg, &mut any.value) {
+                    bail!("failed to encode MsgEthereumHeightVote: {}", e)
+                };
+                any.type_url = context.type_url("MsgEthereumHeightVote");
+                Ok(any)
+            },
+        }
+    }
+
+    fn into_tx_in(self, context: &MsgContext) -> Result<UnsignedTx> {
+        let mut tx = UnsignedTx::new();
+        tx.add_msg(self.into_tx_any_in(context)?);
+
+        Ok(tx)
+    }
+
+    /// Encodes a message that can be a tx Msg on its own, rejecting the bare confirmation variants
+    fn into_tx_any_in(self, context: &MsgContext) -> Result<Any> {
+        // Since we include some confirmation messages in the enum to make getting an Any to insert into SubmitEthereumEventConfirmation
+        // easier, we need to make sure we don't try to submit those directly in a transaction because it's guaranteed to fail.
+        Ok(match self {
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope: _,
+                invalidation_nonce: _,
+                ethereum_signer: _,
+                signature: _,
+            } => bail!("ContractCallTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation"),
+            SommGravity::BatchTxConfirmation {
+                token_contract_address: _,
+                batch_nonce: _,
+                ethereum_signer: _,
+                signature: _,
+            } => bail!("BatchTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation"),
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce: _,
+                ethereum_signer: _,
+                signature: _,
+            } => {
+                bail!("SignerSetTxConfirmation does not represent a transaction Msg. use into_any() to get the Any representation")
+            }
+            _ => self.into_any_in(context)?,
+        })
+    }
+}
+
+/// The proto package the gravity module is served under, `gravity.v1` by default. Encoding through a context
+/// with another package, for a later module version or a fork that renamed it, sets every type URL to
+/// `/<package>.<Msg>`, including those of the confirmation variants.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgContext {
+    package: String,
+}
+
+impl MsgContext {
+    /// Creates a context for `package`, which must be dot separated identifiers such as `gravity.v2`
+    pub fn new(package: impl Into<String>) -> Result<Self> {
+        let package = package.into();
+        let valid = package.split('.').all(|segment| {
+            let mut chars = segment.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if !valid {
+            bail!("invalid proto package {:?}, expected dot separated identifiers such as gravity.v1", package)
+        }
+
+        Ok(Self { package })
+    }
+
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Returns the type URL of `message` in this context's package
+    pub fn type_url(&self, message: &str) -> String {
+        format!("/{}.{}", self.package, message)
+    }
+
+    /// Same as [`ModuleMsg::into_any`], using this context's package for the type URL
+    pub fn into_any(&self, msg: SommGravity<'_>) -> Result<Any> {
+        msg.into_any_in(self)
+    }
+
+    /// Same as [`ModuleMsg::into_tx`], using this context's package for the type URL
+    pub fn into_tx(&self, msg: SommGravity<'_>) -> Result<UnsignedTx> {
+        msg.into_tx_in(self)
+    }
+
+    /// Same as [`msgs_into_tx`], using this context's package for the type URLs
+    pub fn msgs_into_tx(&self, msgs: Vec<SommGravity<'_>>) -> Result<UnsignedTx> {
+        msgs_into_tx_in(msgs, self)
+    }
+}
+
+impl Default for MsgContext {
+    fn default() -> Self {
+        Self {
+            package: "gravity.v1".to_string(),
+        }
+    }
+}
+
+/// Builds an [`UnsignedTx`] containing every message in `msgs`. Messages are added in exactly the order given
+/// and never reordered, so a tx can rely on an earlier message taking effect before a later one, for example
+/// setting delegate keys before submitting a confirmation signed with them. As with [`ModuleMsg::into_tx`],
+/// confirmations must be wrapped in [`SommGravity::SubmitEthereumTxConfirmation`]. The first message that fails
+/// to encode is reported by its index.
+pub fn msgs_into_tx(msgs: Vec<SommGravity<'_>>) -> Result<UnsignedTx> {
+    msgs_into_tx_in(msgs, &MsgContext::default())
+}
+
+fn msgs_into_tx_in(msgs: Vec<SommGravity<'_>>, context: &MsgContext) -> Result<UnsignedTx> {
+    Ok(tx_from_anys(msgs_into_anys(msgs, context)?))
+}
+
+/// Encodes `msgs` in order, the message list of a [`msgs_into_tx`] tx
+fn msgs_into_anys(msgs: Vec<SommGravity<'_>>, context: &MsgContext) -> Result<Vec<Any>> {
+    if msgs.is_empty() {
+        bail!("at least one message is required")
+    }
+
+    let mut anys = Vec::with_capacity(msgs.len());
+    for (i, msg) in msgs.into_iter().enumerate() {
+        match msg.into_tx_any_in(context) {
+            Ok(any) => anys.push(any),
+            Err(e) => bail!("invalid message at index {}: {}", i, e),
+        }
+    }
+
+    Ok(anys)
+}
+
+fn tx_from_anys(anys: Vec<Any>) -> UnsignedTx {
+    let mut tx = UnsignedTx::new();
+    for any in anys {
+        tx.add_msg(any);
+    }
+
+    tx
+}
+
+/// A single transfer to include in a [`send_to_ethereum_batch`] transaction
+#[derive(Clone, Debug)]
+pub struct SendToEthereumParams {
+    pub ethereum_recipient: String,
+    pub amount: Coin,
+    pub bridge_fee: Coin,
+}
+
+/// Builds an [`UnsignedTx`] containing one MsgSendToEthereum per entry in `sends`, all from `sender`, in the
+/// order given as [`msgs_into_tx`] guarantees.
+/// Each entry is checked with [`SommGravity::validate`] while encoding and the first invalid entry is reported
+/// by its index.
+pub fn send_to_ethereum_batch(sends: Vec<SendToEthereumParams>, sender: &str) -> Result<UnsignedTx> {
+    Ok(tx_from_anys(send_to_ethereum_anys(sends, sender)?))
+}
+
+/// Encodes `sends` in order, the message list of a [`send_to_ethereum_batch`] tx
+fn send_to_ethereum_anys(sends: Vec<SendToEthereumParams>, sender: &str) -> Result<Vec<Any>> {
+    if sends.is_empty() {
+        bail!("at least one send is required")
+    }
+
+    let mut anys = Vec::with_capacity(sends.len());
+    for (i, send) in sends.into_iter().enumerate() {
+        let msg = SommGravity::SendToEthereum {
+            sender,
+            ethereum_recipient: &send.ethereum_recipient,
+            amount: send.amount,
+            bridge_fee: send.bridge_fee,
+        };
+        match msg.into_any() {
+            Ok(any) => anys.push(any),
+            Err(e) => bail!("invalid send at index {}: {}", i, e),
+        }
+    }
+
+    Ok(anys)
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_proto::gravity::{MsgCancelSendToEthereum, MsgEthereumHeightVote, MsgSendToEthereum};
+    use prost::Message;
+
+    use super::*;
+
+    const SENDER: &str = "somm1qyqszqgpqyqszqgpqyqszqgpqyqszqgp70wjuk";
+    const ORCHESTRATOR: &str = "somm1qgpqyqszqgpqyqszqgpqyqszqgpqyqsz0tghhq";
+    /// The address of `KEY`
+    const RECIPIENT: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    #[cfg(feature = "serde")]
+    const CONTRACT: &str = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+    const USDC: &str = "gravity0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    fn coin(amount: u128) -> Coin {
+        Coin {
+            denom: USDC.parse().unwrap(),
+            amount,
+        }
+    }
+
+    fn key() -> k256::ecdsa::SigningKey {
+        k256::ecdsa::SigningKey::from_slice(&hex::decode(KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn set_delegate_keys_checks_signature() {
+        let signature = crate::signature::sign_delegate_keys(&key(), SENDER, 4).unwrap();
+        assert!(SommGravity::set_delegate_keys(SENDER, ORCHESTRATOR, RECIPIENT, signature.clone(), 4).is_ok());
+
+        let err = SommGravity::set_delegate_keys(SENDER, ORCHESTRATOR, RECIPIENT, signature, 5).unwrap_err();
+        assert!(err.to_string().contains("was not made by"), "{}", err);
+    }
+
+    #[test]
+    fn set_delegate_keys_variant_encodes_without_nonce() {
+        let msg = SommGravity::SetDelegateKeys {
+            validator_address: SENDER,
+            orchestrator_address: ORCHESTRATOR,
+            ethereum_address: RECIPIENT,
+            eth_signature: vec![0x11; 65],
+        };
+        assert!(msg.into_any().is_ok());
+    }
+
+    #[test]
+    fn msgs_into_tx_preserves_order() {
+        let msgs = vec![
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 3 },
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height: 100,
+                signer: SENDER,
+            },
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 1 },
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 2 },
+        ];
+        let anys = msgs_into_anys(msgs, &MsgContext::default()).unwrap();
+
+        let type_urls: Vec<&str> = anys.iter().map(|a| a.type_url.as_str()).collect();
+        assert_eq!(
+            type_urls,
+            [
+                "/gravity.v1.MsgCancelSendToEthereum",
+                "/gravity.v1.MsgEthereumHeightVote",
+                "/gravity.v1.MsgCancelSendToEthereum",
+                "/gravity.v1.MsgCancelSendToEthereum",
+            ]
+        );
+        let ids: Vec<u64> = [&anys[0], &anys[2], &anys[3]]
+            .iter()
+            .map(|a| MsgCancelSendToEthereum::decode(a.value.as_slice()).unwrap().id)
+            .collect();
+        assert_eq!(ids, [3, 1, 2]);
+        let vote = MsgEthereumHeightVote::decode(anys[1].value.as_slice()).unwrap();
+        assert_eq!(vote.ethereum_height, 100);
+    }
+
+    #[test]
+    fn msgs_into_tx_reports_invalid_index() {
+        let msgs = vec![
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 1 },
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 0 },
+        ];
+        let err = msgs_into_anys(msgs, &MsgContext::default()).unwrap_err();
+        assert!(err.to_string().contains("index 1"), "{}", err);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_encodes_identically() {
+        use crate::signature::sign_delegate_keys;
+
+        let key = key();
+        let signature = vec![0x11; 65];
+        let confirmation = SommGravity::BatchTxConfirmation {
+            token_contract_address: CONTRACT,
+            batch_nonce: 1,
+            ethereum_signer: RECIPIENT,
+            signature: signature.clone(),
+        }
+        .into_any()
+        .unwrap();
+        let msgs = vec![
+            SommGravity::SendToEthereum {
+                sender: SENDER,
+                ethereum_recipient: RECIPIENT,
+                amount: coin(5),
+                bridge_fee: coin(1),
+            },
+            SommGravity::CancelSendToEthereum { sender: SENDER, id: 1 },
+            SommGravity::RequestBatchTx {
+                denom: USDC,
+                signer: SENDER,
+            },
+            SommGravity::SubmitEthereumTxConfirmation {
+                confirmation,
+                signer: SENDER,
+            },
+            SommGravity::ContractCallTxConfirmation {
+                invalidation_scope: vec![1, 2, 3],
+                invalidation_nonce: 1,
+                ethereum_signer: RECIPIENT,
+                signature: signature.clone(),
+            },
+            SommGravity::BatchTxConfirmation {
+                token_contract_address: CONTRACT,
+                batch_nonce: 1,
+                ethereum_signer: RECIPIENT,
+                signature: signature.clone(),
+            },
+            SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce: 1,
+                ethereum_signer: RECIPIENT,
+                signature,
+            },
+            SommGravity::SubmitEthereumEvent {
+                event: Any {
+                    type_url: "/gravity.v1.SendToCosmosEvent".to_string(),
+                    value: vec![8, 1],
+                },
+                signer: SENDER,
+            },
+            SommGravity::SetDelegateKeys {
+                validator_address: SENDER,
+                orchestrator_address: ORCHESTRATOR,
+                ethereum_address: RECIPIENT,
+                eth_signature: sign_delegate_keys(&key, SENDER, 4).unwrap(),
+            },
+            SommGravity::DelegateKeysSignMsg {
+                validator_address: SENDER,
+                nonce: 4,
+            },
+            SommGravity::SubmitEthereumHeightVote {
+                ethereum_height: 100,
+                signer: SENDER,
+            },
+        ];
+
+        for msg in msgs {
+            let json = serde_json::to_string(&msg).unwrap();
+            let decoded: SommGravity<'_> = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded.into_any().unwrap(), msg.into_any().unwrap(), "{}", json);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_escaped_borrowed_strings() {
+        let json = format!(r#"{{"CancelSendToEthereum":{{"sender":"\u0073{}","id":1}}}}"#, &SENDER[1..]);
+        assert!(serde_json::from_str::<SommGravity<'_>>(&json).is_err());
+    }
+
+    #[test]
+    fn send_to_ethereum_batch_preserves_order() {
+        let sends = [5u128, 1, 3]
+            .iter()
+            .map(|&amount| SendToEthereumParams {
+                ethereum_recipient: RECIPIENT.to_string(),
+                amount: coin(amount),
+                bridge_fee: coin(amount * 10),
+            })
+            .collect();
+        let anys = send_to_ethereum_anys(sends, SENDER).unwrap();
+
+        let amounts: Vec<(String, String)> = anys
+            .iter()
+            .map(|a| {
+                assert_eq!(a.type_url, "/gravity.v1.MsgSendToEthereum");
+                let msg = MsgSendToEthereum::decode(a.value.as_slice()).unwrap();
+                (msg.amount.unwrap().amount, msg.bridge_fee.unwrap().amount)
+            })
+            .collect();
+        assert_eq!(
+            amounts,
+            [
+                ("5".to_string(), "50".to_string()),
+                ("1".to_string(), "10".to_string()),
+                ("3".to_string(), "30".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Hooks for observing the lifecycle of an orchestrator confirmation
+use crate::address::EthAddress;
+
+/// The kind of outgoing tx a confirmation is for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConfirmationKind {
+    Batch,
+    SignerSet,
+    ContractCall,
+}
+
+/// Receives lifecycle events for confirmations as they move from checkpoint to submission. Every method has a
+/// no-op default, so implementors only override the points they care about. Use [`NoopObserver`] where an
+/// observer is required but none is wanted.
+pub trait ConfirmationObserver: Send + Sync {
+    /// The checkpoint for the tx identified by `kind` and `nonce` was computed
+    fn checkpoint_computed(&self, _kind: ConfirmationKind, _nonce: u64, _checkpoint: &[u8; 32]) {}
+
+    /// The checkpoint was signed by `signer`
+    fn signed(&self, _kind: ConfirmationKind, _nonce: u64, _signer: &EthAddress) {}
+
+    /// The signed confirmation was built into a tx for submission
+    fn queued(&self, _kind: ConfirmationKind, _nonce: u64) {}
+
+    /// The tx with hash `tx_hash` carrying the confirmation was included in a block
+    fn submitted(&self, _kind: ConfirmationKind, _nonce: u64, _tx_hash: &str) {}
+}
+
+/// An observer that ignores every event
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopObserver;
+
+impl ConfirmationObserver for NoopObserver {}
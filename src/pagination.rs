@@ -0,0 +1,103 @@
+//! A builder for the [`PageRequest`] taken by the paginated queries, and the limits applied when paging
+//! through every result
+use std::time::{Duration, Instant};
+
+pub use ocular::grpc::PageRequest;
+
+use crate::{bail, Result};
+
+/// Starts a [`PageBuilder`] for pages of `limit` results
+pub fn page(limit: u64) -> PageBuilder {
+    PageBuilder {
+        limit,
+        offset: None,
+        key: None,
+        count_total: false,
+        reverse: false,
+    }
+}
+
+/// Builds a [`PageRequest`] for manual pagination, for example
+/// `query_batch_txs(Some(page(50).offset(100).build()?))`
+#[derive(Clone, Debug)]
+pub struct PageBuilder {
+    limit: u64,
+    offset: Option<u64>,
+    key: Option<Vec<u8>>,
+    count_total: bool,
+    reverse: bool,
+}
+
+impl PageBuilder {
+    /// Skips the first `offset` results. Cannot be combined with [`PageBuilder::key`].
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Continues from the `next_key` of a previous page. Cannot be combined with [`PageBuilder::offset`].
+    pub fn key(mut self, key: Vec<u8>) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Asks the node to include the total result count in the response
+    pub fn count_total(mut self) -> Self {
+        self.count_total = true;
+        self
+    }
+
+    /// Returns results in descending order
+    pub fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    pub fn build(self) -> Result<PageRequest> {
+        if self.limit == 0 {
+            bail!("page limit must be non-zero")
+        }
+        if self.offset.is_some() && self.key.is_some() {
+            bail!("a page request may set either an offset or a key, not both")
+        }
+
+        Ok(PageRequest {
+            key: self.key.unwrap_or_default(),
+            offset: self.offset.unwrap_or_default(),
+            limit: self.limit,
+            count_total: self.count_total,
+            reverse: self.reverse,
+        })
+    }
+}
+
+/// Bounds on how far the `query_all_*` aggregators page before giving up, protecting callers from a node that
+/// keeps returning a `next_key`. Exceeding either bound fails the aggregation with an error rather than
+/// returning partial results. Set them per connection with
+/// [`GravitySession::with_page_limits`](crate::session::GravitySession::with_page_limits).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageLimits {
+    /// The most pages one aggregation fetches. Defaults to 1000, or 100,000 results at the usual page size.
+    pub max_pages: usize,
+    /// The total time one aggregation may take across all of its page requests. Defaults to 5 minutes.
+    pub timeout: Duration,
+}
+
+impl PageLimits {
+    /// Returns these limits with the timeout shortened, if needed, to end at `deadline`
+    pub fn until(self, deadline: Instant) -> Self {
+        Self {
+            timeout: self.timeout.min(deadline.saturating_duration_since(Instant::now())),
+            ..self
+        }
+    }
+}
+
+impl Default for PageLimits {
+    fn default() -> Self {
+        Self {
+            max_pages: 1000,
+            timeout: Duration::from_secs(300),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Opt-in Ethereum side checks run before submitting a send, using an `ethers` provider
+use ethers::{providers::Middleware, types::H160};
+
+use crate::{address::EthAddress, bail, Result};
+
+/// A non-fatal concern about a send's Ethereum recipient. Some contracts accept bridged ERC20 transfers, so
+/// this is surfaced for the caller to decide on rather than returned as an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecipientWarning {
+    pub recipient: EthAddress,
+    /// Size in bytes of the code deployed at the recipient
+    pub code_size: usize,
+    pub message: String,
+}
+
+/// Checks whether `ethereum_recipient` has contract code deployed at the latest block, returning a
+/// [`RecipientWarning`] if so. Errors only if the address is malformed or the provider call fails.
+pub async fn check_recipient<M: Middleware>(
+    provider: &M,
+    ethereum_recipient: &str,
+) -> Result<Option<RecipientWarning>> {
+    let recipient = ethereum_recipient.parse::<EthAddress>()?;
+    let code = match provider.get_code(H160::from(*recipient.as_bytes()), None).await {
+        Ok(code) => code,
+        Err(e) => bail!("failed to fetch code at {}: {}", recipient, e),
+    };
+
+    if code.as_ref().is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(RecipientWarning {
+        recipient,
+        code_size: code.as_ref().len(),
+        message: format!(
+            "recipient {} is a contract; bridged tokens may be unrecoverable if it cannot handle ERC20 transfers",
+            recipient
+        ),
+    }))
+}
@@ -0,0 +1,92 @@
+//! A reusable connection shared between the gravity queries and other module clients.
+//!
+//! Every [`SommGravityExt`](crate::extension::SommGravityExt) method called on a [`GrpcClient`] dials the
+//! endpoint anew. A [`GravitySession`] dials once and hands out clones of the same [`Channel`], which are
+//! cheap and multiplex over the one underlying connection. Hold a session for the gravity queries and pass
+//! [`GravitySession::channel`] to any other tonic generated query client, for example:
+//!
+//! ```ignore
+//! let session = GravitySession::from_client(&grpc_client).await?;
+//! let params = session.query_somm_gravity_params().await?;
+//! let mut bank = cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient::new(session.channel());
+//! ```
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ocular::grpc::GrpcClient;
+use tokio::sync::OwnedSemaphorePermit;
+use tonic::transport::Channel;
+
+use crate::{
+    connections::acquire_connection,
+    endpoint::validate_endpoint,
+    extension::{BankQueryClient, GravityConnection, SommGravityQueryClient},
+    pagination::PageLimits,
+    Result,
+};
+
+/// A single gRPC channel to a node, shared by every query made through it
+#[derive(Clone, Debug)]
+pub struct GravitySession {
+    endpoint: String,
+    channel: Channel,
+    page_limits: PageLimits,
+    /// Held until the last clone is dropped, if a global connection limit is set
+    _permit: Option<Arc<OwnedSemaphorePermit>>,
+}
+
+impl GravitySession {
+    /// Connects to `endpoint`, failing if it isn't a valid endpoint URL (see [`validate_endpoint`]) or the
+    /// connection cannot be established. Waits for room first if a global connection limit is set.
+    pub async fn connect(endpoint: &str) -> Result<Self> {
+        validate_endpoint(endpoint)?;
+        let permit = acquire_connection().await;
+        let channel = Channel::from_shared(endpoint.to_string())?.connect().await?;
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            channel,
+            page_limits: PageLimits::default(),
+            _permit: permit.map(Arc::new),
+        })
+    }
+
+    /// Connects to the gRPC endpoint of an existing [`GrpcClient`]
+    pub async fn from_client(client: &GrpcClient) -> Result<Self> {
+        Self::connect(&client.grpc_endpoint()).await
+    }
+
+    /// Sets the limits applied to the `query_all_*` aggregators made through this session
+    pub fn with_page_limits(mut self, page_limits: PageLimits) -> Self {
+        self.page_limits = page_limits;
+        self
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Returns a handle to the shared channel for use by other module clients
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+}
+
+#[async_trait(?Send)]
+impl GravityConnection for GravitySession {
+    async fn gravity_client(&self) -> Result<SommGravityQueryClient> {
+        Ok(SommGravityQueryClient::from_channel(self.channel()))
+    }
+
+    fn gravity_endpoint(&self) -> String {
+        self.endpoint.clone()
+    }
+
+    fn page_limits(&self) -> PageLimits {
+        self.page_limits
+    }
+
+    async fn bank_client(&self) -> Result<BankQueryClient> {
+        Ok(BankQueryClient::from_channel(self.channel()))
+    }
+}
@@ -0,0 +1,420 @@
+//! Ethereum signing, signature recovery and verification for gravity confirmations and delegate keys
+//!
+//! Nothing here touches the network. Every checkpoint, signing and verification helper takes the bridge's
+//! `gravity_id` as a parameter, so a bulk signer can resolve it once with
+//! [`SommGravityExt::query_gravity_id`](crate::extension::SommGravityExt::query_gravity_id) and sign any number
+//! of confirmations offline. This relies on the id being immutable: it is fixed when the bridge contract is
+//! deployed, and a chain that changed it would orphan every existing checkpoint. The
+//! `SommGravityExt::fetch_and_sign_*` methods wrap the fetch and sign in one call for one-off use.
+use gravity_proto::gravity::{
+    BatchTx, BatchTxConfirmation, ContractCallTx, ContractCallTxConfirmation, DelegateKeysSignMsg, SignerSetTx,
+    SignerSetTxConfirmation,
+};
+use k256::{
+    ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey},
+    elliptic_curve::sec1::ToEncodedPoint,
+    PublicKey,
+};
+
+use crate::{
+    address::EthAddress,
+    bail,
+    checkpoint::{batch_checkpoint, contract_call_checkpoint, keccak256, signer_set_checkpoint},
+    observer::{ConfirmationKind, ConfirmationObserver, NoopObserver},
+    Result,
+};
+
+/// Derives the Ethereum address of a secp256k1 public key, the last 20 bytes of the keccak256 of its
+/// uncompressed encoding without the leading `0x04`
+pub fn eth_address_from_pubkey(pubkey: &PublicKey) -> EthAddress {
+    let point = pubkey.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+
+    EthAddress::from_bytes(address)
+}
+
+/// Returns the hash orchestrators actually sign for a checkpoint, the checkpoint behind the
+/// `"\x19Ethereum Signed Message:\n32"` prefix as `Gravity.sol` verifies it
+pub fn ethereum_message_hash(checkpoint: &[u8; 32]) -> [u8; 32] {
+    let mut message = Vec::with_capacity(60);
+    message.extend_from_slice(b"\x19Ethereum Signed Message:\n32");
+    message.extend_from_slice(checkpoint);
+
+    keccak256(&message)
+}
+
+/// Recovers the Ethereum address that produced a 65 byte `r || s || v` signature over `checkpoint`. `v` may be
+/// given as 27/28 or 0/1.
+pub fn recover_signer(checkpoint: &[u8; 32], signature: &[u8]) -> Result<EthAddress> {
+    if signature.len() != 65 {
+        bail!("invalid signature: expected 65 bytes, got {}", signature.len())
+    }
+
+    let sig = match Signature::from_slice(&signature[..64]) {
+        Ok(s) => s,
+        Err(e) => bail!("invalid signature: {}", e),
+    };
+    let v = signature[64];
+    let recovery_id = match RecoveryId::from_byte(if v >= 27 { v - 27 } else { v }) {
+        Some(id) => id,
+        None => bail!("invalid signature: bad recovery byte {}", v),
+    };
+
+    let digest = ethereum_message_hash(checkpoint);
+    let key = match VerifyingKey::recover_from_prehash(&digest, &sig, recovery_id) {
+        Ok(k) => k,
+        Err(e) => bail!("failed to recover signer: {}", e),
+    };
+
+    Ok(eth_address_from_pubkey(&PublicKey::from(&key)))
+}
+
+/// Signs `checkpoint` behind the Ethereum signed message prefix, returning the 65 byte `r || s || v` signature
+/// with `v` as 27/28, the form the module and `Gravity.sol` accept
+pub fn sign_checkpoint(key: &SigningKey, checkpoint: &[u8; 32]) -> Result<Vec<u8>> {
+    let digest = ethereum_message_hash(checkpoint);
+    let (signature, recovery_id) = match key.sign_prehash_recoverable(&digest) {
+        Ok(signed) => signed,
+        Err(e) => bail!("failed to sign checkpoint: {}", e),
+    };
+
+    let mut bytes = signature.to_bytes().to_vec();
+    bytes.push(recovery_id.to_byte() + 27);
+
+    Ok(bytes)
+}
+
+/// Signs a batch's checkpoint with `key`, returning the confirmation to submit in a
+/// `MsgSubmitEthereumTxConfirmation`. `ethereum_signer` is derived from the key.
+pub fn sign_batch_confirmation(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    batch: &BatchTx,
+) -> Result<BatchTxConfirmation> {
+    sign_batch_confirmation_with_observer(key, gravity_id, batch, &NoopObserver)
+}
+
+/// Same as [`sign_batch_confirmation`], reporting the checkpoint and signature to `observer`
+pub fn sign_batch_confirmation_with_observer(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    batch: &BatchTx,
+    observer: &dyn ConfirmationObserver,
+) -> Result<BatchTxConfirmation> {
+    let checkpoint = batch_checkpoint(gravity_id, batch)?;
+    observer.checkpoint_computed(ConfirmationKind::Batch, batch.batch_nonce, &checkpoint);
+    let signer = eth_address_from_pubkey(&PublicKey::from(key.verifying_key()));
+    let signature = sign_checkpoint(key, &checkpoint)?;
+    observer.signed(ConfirmationKind::Batch, batch.batch_nonce, &signer);
+
+    Ok(BatchTxConfirmation {
+        token_contract: batch.token_contract.clone(),
+        batch_nonce: batch.batch_nonce,
+        ethereum_signer: signer.to_checksum(),
+        signature,
+    })
+}
+
+/// Signs a signer set's checkpoint with `key`, returning the confirmation to submit in a
+/// `MsgSubmitEthereumTxConfirmation`. `ethereum_signer` is derived from the key.
+pub fn sign_signer_set_confirmation(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    signer_set: &SignerSetTx,
+) -> Result<SignerSetTxConfirmation> {
+    sign_signer_set_confirmation_with_observer(key, gravity_id, signer_set, &NoopObserver)
+}
+
+/// Same as [`sign_signer_set_confirmation`], reporting the checkpoint and signature to `observer`
+pub fn sign_signer_set_confirmation_with_observer(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    signer_set: &SignerSetTx,
+    observer: &dyn ConfirmationObserver,
+) -> Result<SignerSetTxConfirmation> {
+    let checkpoint = signer_set_checkpoint(gravity_id, signer_set)?;
+    observer.checkpoint_computed(ConfirmationKind::SignerSet, signer_set.nonce, &checkpoint);
+    let signer = eth_address_from_pubkey(&PublicKey::from(key.verifying_key()));
+    let signature = sign_checkpoint(key, &checkpoint)?;
+    observer.signed(ConfirmationKind::SignerSet, signer_set.nonce, &signer);
+
+    Ok(SignerSetTxConfirmation {
+        signer_set_nonce: signer_set.nonce,
+        ethereum_signer: signer.to_checksum(),
+        signature,
+    })
+}
+
+/// Signs a contract call's checkpoint with `key`, returning the confirmation to submit in a
+/// `MsgSubmitEthereumTxConfirmation`. `ethereum_signer` is derived from the key.
+pub fn sign_contract_call_confirmation(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    call: &ContractCallTx,
+) -> Result<ContractCallTxConfirmation> {
+    sign_contract_call_confirmation_with_observer(key, gravity_id, call, &NoopObserver)
+}
+
+/// Same as [`sign_contract_call_confirmation`], reporting the checkpoint and signature to `observer`. Events are
+/// reported under the call's invalidation nonce.
+pub fn sign_contract_call_confirmation_with_observer(
+    key: &SigningKey,
+    gravity_id: &[u8; 32],
+    call: &ContractCallTx,
+    observer: &dyn ConfirmationObserver,
+) -> Result<ContractCallTxConfirmation> {
+    let checkpoint = contract_call_checkpoint(gravity_id, call)?;
+    observer.checkpoint_computed(ConfirmationKind::ContractCall, call.invalidation_nonce, &checkpoint);
+    let signer = eth_address_from_pubkey(&PublicKey::from(key.verifying_key()));
+    let signature = sign_checkpoint(key, &checkpoint)?;
+    observer.signed(ConfirmationKind::ContractCall, call.invalidation_nonce, &signer);
+
+    Ok(ContractCallTxConfirmation {
+        invalidation_scope: call.invalidation_scope.clone(),
+        invalidation_nonce: call.invalidation_nonce,
+        ethereum_signer: signer.to_checksum(),
+        signature,
+    })
+}
+
+/// Returns the hash signed to set delegate keys: the keccak256 of the protobuf encoded `DelegateKeysSignMsg`.
+/// `nonce` is the validator account's current sequence.
+pub fn delegate_keys_sign_hash(validator_address: &str, nonce: u64) -> [u8; 32] {
+    let msg = DelegateKeysSignMsg {
+        validator_address: validator_address.to_string(),
+        nonce,
+    };
+
+    keccak256(&prost::Message::encode_to_vec(&msg))
+}
+
+/// Signs the delegate keys message for `validator_address` with the Ethereum key being delegated, returning
+/// the `eth_signature` for a `MsgDelegateKeys`. The key's address is the `ethereum_address` to delegate.
+pub fn sign_delegate_keys(key: &SigningKey, validator_address: &str, nonce: u64) -> Result<Vec<u8>> {
+    sign_checkpoint(key, &delegate_keys_sign_hash(validator_address, nonce))
+}
+
+/// Verifies that a `MsgDelegateKeys` `eth_signature` recovers to `ethereum_address` over the
+/// [`delegate_keys_sign_hash`] of `validator_address` and `nonce`, the validator account's sequence when the
+/// signature was made. Returns false for a malformed or mismatched signature or address.
+pub fn verify_delegate_keys_signature(
+    validator_address: &str,
+    ethereum_address: &str,
+    nonce: u64,
+    eth_signature: &[u8],
+) -> bool {
+    signed_by(
+        &delegate_keys_sign_hash(validator_address, nonce),
+        eth_signature,
+        ethereum_address,
+    )
+}
+
+/// Verifies that a batch confirmation was signed by the `ethereum_signer` it claims, by recomputing the batch
+/// checkpoint and recovering the signer from `confirmation.signature`.
+///
+/// Returns `Ok(false)` for a malformed or mismatched signature or signer. Errors if the confirmation is for a
+/// different batch than `batch`, or if the batch's checkpoint can't be computed.
+pub fn verify_batch_confirmation(
+    batch: &BatchTx,
+    gravity_id: &[u8; 32],
+    confirmation: &BatchTxConfirmation,
+) -> Result<bool> {
+    if confirmation.batch_nonce != batch.batch_nonce
+        || !confirmation
+            .token_contract
+            .eq_ignore_ascii_case(&batch.token_contract)
+    {
+        bail!(
+            "confirmation is for batch {} nonce {}, not {} nonce {}",
+            confirmation.token_contract,
+            confirmation.batch_nonce,
+            batch.token_contract,
+            batch.batch_nonce
+        )
+    }
+
+    let checkpoint = batch_checkpoint(gravity_id, batch)?;
+
+    Ok(signed_by(&checkpoint, &confirmation.signature, &confirmation.ethereum_signer))
+}
+
+/// Verifies that a signer set confirmation was signed by the `ethereum_signer` it claims. See
+/// [`verify_batch_confirmation`].
+pub fn verify_signer_set_confirmation(
+    signer_set: &SignerSetTx,
+    gravity_id: &[u8; 32],
+    confirmation: &SignerSetTxConfirmation,
+) -> Result<bool> {
+    if confirmation.signer_set_nonce != signer_set.nonce {
+        bail!(
+            "confirmation is for signer set {}, not {}",
+            confirmation.signer_set_nonce,
+            signer_set.nonce
+        )
+    }
+
+    let checkpoint = signer_set_checkpoint(gravity_id, signer_set)?;
+
+    Ok(signed_by(&checkpoint, &confirmation.signature, &confirmation.ethereum_signer))
+}
+
+/// Verifies that a contract call confirmation was signed by the `ethereum_signer` it claims. See
+/// [`verify_batch_confirmation`].
+pub fn verify_contract_call_confirmation(
+    call: &ContractCallTx,
+    gravity_id: &[u8; 32],
+    confirmation: &ContractCallTxConfirmation,
+) -> Result<bool> {
+    if confirmation.invalidation_scope != call.invalidation_scope
+        || confirmation.invalidation_nonce != call.invalidation_nonce
+    {
+        bail!(
+            "confirmation is for contract call scope 0x{} nonce {}, not scope 0x{} nonce {}",
+            hex::encode(&confirmation.invalidation_scope),
+            confirmation.invalidation_nonce,
+            hex::encode(&call.invalidation_scope),
+            call.invalidation_nonce
+        )
+    }
+
+    let checkpoint = contract_call_checkpoint(gravity_id, call)?;
+
+    Ok(signed_by(&checkpoint, &confirmation.signature, &confirmation.ethereum_signer))
+}
+
+/// Returns whether `signature` over `checkpoint` recovers to `ethereum_signer`, treating a malformed signer or
+/// signature as a mismatch
+fn signed_by(checkpoint: &[u8; 32], signature: &[u8], ethereum_signer: &str) -> bool {
+    let claimed = match ethereum_signer.parse::<EthAddress>() {
+        Ok(a) => a,
+        Err(_) => return false,
+    };
+
+    recover_signer(checkpoint, signature).map_or(false, |signer| signer == claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use gravity_proto::gravity::{Erc20Token, SendToEthereum};
+
+    use super::*;
+
+    /// The widely used example key whose address is `0x2c7536E3605D9C16a7a3D7b1898e529396a65c23`
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const SIGNER: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    /// keccak256 of `"ocular-somm-gravity test checkpoint"`, signed by `KEY` behind the Ethereum message prefix
+    const CHECKPOINT: &str = "15eaa706daeb83fe6431732447dbcfb75fc7214f8f22d70621a07cd0cb307193";
+    const SIGNATURE: &str = "f973a0b87062c389d125d8199e803b832b6ac6bf7867a4f6cd87506060fc4c58\
+                             678043b57614bc3bb624c4570e6b386114a53fc0584f1cbf960ce461880425571b";
+
+    fn checkpoint() -> [u8; 32] {
+        let mut checkpoint = [0u8; 32];
+        checkpoint.copy_from_slice(&hex::decode(CHECKPOINT).unwrap());
+        checkpoint
+    }
+
+    fn signer() -> EthAddress {
+        SIGNER.parse().unwrap()
+    }
+
+    fn key() -> SigningKey {
+        SigningKey::from_slice(&hex::decode(KEY).unwrap()).unwrap()
+    }
+
+    fn batch() -> BatchTx {
+        BatchTx {
+            batch_nonce: 7,
+            timeout: 1_000,
+            transactions: vec![SendToEthereum {
+                id: 1,
+                ethereum_recipient: SIGNER.to_string(),
+                erc20_token: Some(Erc20Token {
+                    contract: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+                    amount: "1000000".to_string(),
+                }),
+                erc20_fee: Some(Erc20Token {
+                    contract: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+                    amount: "100".to_string(),
+                }),
+                ..Default::default()
+            }],
+            token_contract: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string(),
+            height: 100,
+        }
+    }
+
+    #[test]
+    fn eth_address_from_pubkey_known_keys() {
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        let key = SigningKey::from_slice(&one).unwrap();
+        let address = eth_address_from_pubkey(&PublicKey::from(key.verifying_key()));
+        assert_eq!(address.to_checksum(), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+
+        assert_eq!(eth_address_from_pubkey(&PublicKey::from(key().verifying_key())), signer());
+    }
+
+    #[test]
+    fn recover_signer_known_vector() {
+        let signature = hex::decode(SIGNATURE).unwrap();
+        assert_eq!(recover_signer(&checkpoint(), &signature).unwrap(), signer());
+    }
+
+    #[test]
+    fn recover_signer_accepts_zero_based_recovery_byte() {
+        let mut signature = hex::decode(SIGNATURE).unwrap();
+        signature[64] -= 27;
+        assert_eq!(recover_signer(&checkpoint(), &signature).unwrap(), signer());
+    }
+
+    #[test]
+    fn recover_signer_rejects_wrong_length() {
+        let signature = hex::decode(SIGNATURE).unwrap();
+        assert!(recover_signer(&checkpoint(), &signature[..64]).is_err());
+    }
+
+    #[test]
+    fn flipped_byte_does_not_verify() {
+        let signature = hex::decode(SIGNATURE).unwrap();
+        assert!(signed_by(&checkpoint(), &signature, SIGNER));
+
+        let mut tampered_checkpoint = checkpoint();
+        tampered_checkpoint[0] ^= 0x01;
+        assert!(!signed_by(&tampered_checkpoint, &signature, SIGNER));
+
+        let mut tampered_signature = signature;
+        tampered_signature[40] ^= 0x01;
+        assert!(!signed_by(&checkpoint(), &tampered_signature, SIGNER));
+    }
+
+    #[test]
+    fn sign_checkpoint_recovers_to_key() {
+        let signature = sign_checkpoint(&key(), &checkpoint()).unwrap();
+        assert_eq!(signature.len(), 65);
+        assert!(signature[64] == 27 || signature[64] == 28);
+        assert_eq!(recover_signer(&checkpoint(), &signature).unwrap(), signer());
+    }
+
+    #[test]
+    fn verify_batch_confirmation_round_trip() {
+        let gravity_id = [0x42u8; 32];
+        let batch = batch();
+        let confirmation = sign_batch_confirmation(&key(), &gravity_id, &batch).unwrap();
+        assert_eq!(confirmation.ethereum_signer, SIGNER);
+        assert!(verify_batch_confirmation(&batch, &gravity_id, &confirmation).unwrap());
+
+        let mut tampered = confirmation.clone();
+        tampered.signature[10] ^= 0x01;
+        assert!(!verify_batch_confirmation(&batch, &gravity_id, &tampered).unwrap());
+
+        let other_id = [0x43u8; 32];
+        assert!(!verify_batch_confirmation(&batch, &other_id, &confirmation).unwrap());
+
+        let mut other_batch = batch;
+        other_batch.batch_nonce += 1;
+        assert!(verify_batch_confirmation(&other_batch, &gravity_id, &confirmation).is_err());
+    }
+}
@@ -0,0 +1,675 @@
+//! Serializable mirrors of the gravity state types, used to export module state for offline analysis.
+//! Byte fields are encoded as `0x` prefixed hex.
+//!
+//! State can be exported whole as a [`GravityStateSnapshot`], or streamed as newline delimited JSON for chains
+//! whose history is too large to hold in memory. Each line of an NDJSON export is one [`StateRecord`], an object
+//! of the form `{"section": <name>, "record": <value>}`:
+//!
+//! | `section`                    | `record`                                |
+//! |------------------------------|-----------------------------------------|
+//! | `header`                     | [`ExportHeader`], always the first line |
+//! | `params`                     | [`ParamsSnapshot`]                      |
+//! | `latest_signer_set`          | [`SignerSetTxSnapshot`]                 |
+//! | `batch`                      | [`BatchTxSnapshot`]                     |
+//! | `batch_confirmation`         | [`BatchTxConfirmationSnapshot`]         |
+//! | `contract_call`              | [`ContractCallTxSnapshot`]              |
+//! | `contract_call_confirmation` | [`ContractCallTxConfirmationSnapshot`]  |
+//! | `delegate_keys`              | [`DelegateKeysSnapshot`]                |
+//! | `error`                      | [`SectionError`]                        |
+//!
+//! Sections are written in the order of the table. Confirmations directly follow the batch or contract call
+//! they confirm. A query failure is written as an `error` record and the export moves on to the next section,
+//! so a section may be partially written before its error. Importers can parse the stream a line at a time with
+//! `serde_json::from_str::<StateRecord>`.
+#[cfg(feature = "query")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "query")]
+use futures::{
+    future::LocalBoxFuture,
+    io::{AsyncWrite, AsyncWriteExt},
+    stream, FutureExt, StreamExt,
+};
+use gravity_proto::gravity as proto;
+#[cfg(feature = "query")]
+use ocular::grpc::PageRequest;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "query")]
+use crate::{
+    bail,
+    extension::{GravityConnection, SommGravityExt},
+};
+use crate::Result;
+
+/// Options for [`SommGravityExt::export_state_with`](crate::extension::SommGravityExt::export_state_with)
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// The maximum number of sections queried at once. Defaults to 5, which queries every section concurrently.
+    pub max_concurrency: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { max_concurrency: 5 }
+    }
+}
+
+/// A point in time dump of the gravity module state. Sections that failed to export are `None` and have an
+/// entry in `errors`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GravityStateSnapshot {
+    /// The endpoint the state was read from
+    pub endpoint: String,
+    /// Seconds since the unix epoch at which the export started
+    pub captured_at: u64,
+    pub params: Option<ParamsSnapshot>,
+    pub latest_signer_set: Option<SignerSetTxSnapshot>,
+    pub batches: Option<Vec<BatchTxSnapshot>>,
+    pub contract_calls: Option<Vec<ContractCallTxSnapshot>>,
+    pub delegate_keys: Option<Vec<DelegateKeysSnapshot>>,
+    pub errors: Vec<SectionError>,
+}
+
+impl GravityStateSnapshot {
+    /// Serializes the snapshot as pretty printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserializes a snapshot previously produced by [`GravityStateSnapshot::to_json`]
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Writes the snapshot as gzip compressed JSON, returning the writer once the stream is finished. Async
+    /// sinks can be fed by compressing into a `Vec<u8>` first.
+    #[cfg(feature = "gzip")]
+    pub fn write_gzip<W: std::io::Write>(&self, writer: W) -> Result<W> {
+        let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+
+        Ok(encoder.finish()?)
+    }
+}
+
+/// Reads a snapshot written by [`GravityStateSnapshot::write_gzip`]
+#[cfg(feature = "gzip")]
+pub fn import_state<R: std::io::Read>(reader: R) -> Result<GravityStateSnapshot> {
+    let decoder = flate2::read::GzDecoder::new(reader);
+
+    Ok(serde_json::from_reader(decoder)?)
+}
+
+/// A section of a snapshot that could not be exported
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SectionError {
+    pub section: String,
+    pub error: String,
+}
+
+/// One line of an NDJSON state export. See the [module docs](self) for the schema.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "section", content = "record", rename_all = "snake_case")]
+pub enum StateRecord {
+    Header(ExportHeader),
+    Params(ParamsSnapshot),
+    LatestSignerSet(SignerSetTxSnapshot),
+    Batch(BatchTxSnapshot),
+    BatchConfirmation(BatchTxConfirmationSnapshot),
+    ContractCall(ContractCallTxSnapshot),
+    ContractCallConfirmation(ContractCallTxConfirmationSnapshot),
+    DelegateKeys(DelegateKeysSnapshot),
+    Error(SectionError),
+}
+
+/// The first record of an NDJSON state export
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportHeader {
+    /// The endpoint the state was read from
+    pub endpoint: String,
+    /// Seconds since the unix epoch at which the export started
+    pub captured_at: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParamsSnapshot {
+    pub gravity_id: String,
+    pub contract_source_hash: String,
+    pub bridge_ethereum_address: String,
+    pub bridge_chain_id: u64,
+    pub signed_signer_set_txs_window: u64,
+    pub signed_batches_window: u64,
+    pub ethereum_signatures_window: u64,
+    pub target_eth_tx_timeout: u64,
+    pub average_block_time: u64,
+    pub average_ethereum_block_time: u64,
+    pub unbond_slashing_signer_set_txs_window: u64,
+    pub bridge_active: bool,
+}
+
+impl From<&proto::Params> for ParamsSnapshot {
+    fn from(params: &proto::Params) -> Self {
+        Self {
+            gravity_id: params.gravity_id.clone(),
+            contract_source_hash: params.contract_source_hash.clone(),
+            bridge_ethereum_address: params.bridge_ethereum_address.clone(),
+            bridge_chain_id: params.bridge_chain_id,
+            signed_signer_set_txs_window: params.signed_signer_set_txs_window,
+            signed_batches_window: params.signed_batches_window,
+            ethereum_signatures_window: params.ethereum_signatures_window,
+            target_eth_tx_timeout: params.target_eth_tx_timeout,
+            average_block_time: params.average_block_time,
+            average_ethereum_block_time: params.average_ethereum_block_time,
+            unbond_slashing_signer_set_txs_window: params.unbond_slashing_signer_set_txs_window,
+            bridge_active: params.bridge_active,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthereumSignerSnapshot {
+    pub power: u64,
+    pub ethereum_address: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerSetTxSnapshot {
+    pub nonce: u64,
+    pub height: u64,
+    pub signers: Vec<EthereumSignerSnapshot>,
+}
+
+impl From<&proto::SignerSetTx> for SignerSetTxSnapshot {
+    fn from(signer_set: &proto::SignerSetTx) -> Self {
+        Self {
+            nonce: signer_set.nonce,
+            height: signer_set.height,
+            signers: signer_set
+                .signers
+                .iter()
+                .map(|s| EthereumSignerSnapshot {
+                    power: s.power,
+                    ethereum_address: s.ethereum_address.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Erc20TokenSnapshot {
+    pub contract: String,
+    pub amount: String,
+}
+
+impl From<&proto::Erc20Token> for Erc20TokenSnapshot {
+    fn from(token: &proto::Erc20Token) -> Self {
+        Self {
+            contract: token.contract.clone(),
+            amount: token.amount.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendToEthereumSnapshot {
+    pub id: u64,
+    pub sender: String,
+    pub ethereum_recipient: String,
+    pub erc20_token: Option<Erc20TokenSnapshot>,
+    pub erc20_fee: Option<Erc20TokenSnapshot>,
+}
+
+impl From<&proto::SendToEthereum> for SendToEthereumSnapshot {
+    fn from(send: &proto::SendToEthereum) -> Self {
+        Self {
+            id: send.id,
+            sender: send.sender.clone(),
+            ethereum_recipient: send.ethereum_recipient.clone(),
+            erc20_token: send.erc20_token.as_ref().map(Into::into),
+            erc20_fee: send.erc20_fee.as_ref().map(Into::into),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchTxSnapshot {
+    pub batch_nonce: u64,
+    pub timeout: u64,
+    pub transactions: Vec<SendToEthereumSnapshot>,
+    pub token_contract: String,
+    pub height: u64,
+}
+
+impl From<&proto::BatchTx> for BatchTxSnapshot {
+    fn from(batch: &proto::BatchTx) -> Self {
+        Self {
+            batch_nonce: batch.batch_nonce,
+            timeout: batch.timeout,
+            transactions: batch.transactions.iter().map(Into::into).collect(),
+            token_contract: batch.token_contract.clone(),
+            height: batch.height,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCallTxSnapshot {
+    pub invalidation_nonce: u64,
+    #[serde(with = "hex_bytes")]
+    pub invalidation_scope: Vec<u8>,
+    pub address: String,
+    #[serde(with = "hex_bytes")]
+    pub payload: Vec<u8>,
+    pub timeout: u64,
+    pub tokens: Vec<Erc20TokenSnapshot>,
+    pub fees: Vec<Erc20TokenSnapshot>,
+    pub height: u64,
+}
+
+impl From<&proto::ContractCallTx> for ContractCallTxSnapshot {
+    fn from(call: &proto::ContractCallTx) -> Self {
+        Self {
+            invalidation_nonce: call.invalidation_nonce,
+            invalidation_scope: call.invalidation_scope.clone(),
+            address: call.address.clone(),
+            payload: call.payload.clone(),
+            timeout: call.timeout,
+            tokens: call.tokens.iter().map(Into::into).collect(),
+            fees: call.fees.iter().map(Into::into).collect(),
+            height: call.height,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegateKeysSnapshot {
+    pub validator_address: String,
+    pub orchestrator_address: String,
+    pub ethereum_address: String,
+    #[serde(with = "hex_bytes")]
+    pub eth_signature: Vec<u8>,
+}
+
+impl From<&proto::MsgDelegateKeys> for DelegateKeysSnapshot {
+    fn from(keys: &proto::MsgDelegateKeys) -> Self {
+        Self {
+            validator_address: keys.validator_address.clone(),
+            orchestrator_address: keys.orchestrator_address.clone(),
+            ethereum_address: keys.ethereum_address.clone(),
+            eth_signature: keys.eth_signature.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchTxConfirmationSnapshot {
+    pub token_contract: String,
+    pub batch_nonce: u64,
+    pub ethereum_signer: String,
+    #[serde(with = "hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl From<&proto::BatchTxConfirmation> for BatchTxConfirmationSnapshot {
+    fn from(confirmation: &proto::BatchTxConfirmation) -> Self {
+        Self {
+            token_contract: confirmation.token_contract.clone(),
+            batch_nonce: confirmation.batch_nonce,
+            ethereum_signer: confirmation.ethereum_signer.clone(),
+            signature: confirmation.signature.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCallTxConfirmationSnapshot {
+    #[serde(with = "hex_bytes")]
+    pub invalidation_scope: Vec<u8>,
+    pub invalidation_nonce: u64,
+    pub ethereum_signer: String,
+    #[serde(with = "hex_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl From<&proto::ContractCallTxConfirmation> for ContractCallTxConfirmationSnapshot {
+    fn from(confirmation: &proto::ContractCallTxConfirmation) -> Self {
+        Self {
+            invalidation_scope: confirmation.invalidation_scope.clone(),
+            invalidation_nonce: confirmation.invalidation_nonce,
+            ethereum_signer: confirmation.ethereum_signer.clone(),
+            signature: confirmation.signature.clone(),
+        }
+    }
+}
+
+/// Serializes byte vectors as `0x` prefixed hex strings
+pub(crate) mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(D::Error::custom)
+    }
+}
+
+/// Serializes a [`Coin`](ocular::cosmrs::Coin) as `{"denom": <denom>, "amount": <decimal string>}`, as cosmos JSON
+/// does
+pub(crate) mod coin {
+    use ocular::cosmrs::{Coin, Denom};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct CoinJson {
+        denom: String,
+        amount: String,
+    }
+
+    pub fn serialize<S: Serializer>(coin: &Coin, serializer: S) -> Result<S::Ok, S::Error> {
+        CoinJson {
+            denom: coin.denom.to_string(),
+            amount: coin.amount.to_string(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Coin, D::Error> {
+        let coin = CoinJson::deserialize(deserializer)?;
+
+        Ok(Coin {
+            denom: coin.denom.parse::<Denom>().map_err(D::Error::custom)?,
+            amount: coin.amount.parse::<u128>().map_err(D::Error::custom)?,
+        })
+    }
+}
+
+/// Serializes an [`Any`](prost_types::Any) as `{"type_url": <type url>, "value": <0x prefixed hex>}`
+pub(crate) mod any {
+    use prost_types::Any;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct AnyJson {
+        type_url: String,
+        #[serde(with = "super::hex_bytes")]
+        value: Vec<u8>,
+    }
+
+    pub fn serialize<S: Serializer>(any: &Any, serializer: S) -> Result<S::Ok, S::Error> {
+        AnyJson {
+            type_url: any.type_url.clone(),
+            value: any.value.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Any, D::Error> {
+        let any = AnyJson::deserialize(deserializer)?;
+
+        Ok(Any {
+            type_url: any.type_url,
+            value: any.value,
+        })
+    }
+}
+
+#[cfg(feature = "query")]
+enum ExportSection {
+    Params(ParamsSnapshot),
+    LatestSignerSet(SignerSetTxSnapshot),
+    Batches(Vec<BatchTxSnapshot>),
+    ContractCalls(Vec<ContractCallTxSnapshot>),
+    DelegateKeys(Vec<DelegateKeysSnapshot>),
+}
+
+/// Gathers every snapshot section concurrently, recording sections that fail rather than aborting the export
+#[cfg(feature = "query")]
+pub(crate) async fn export_state<T>(client: &T, options: &ExportOptions) -> Result<GravityStateSnapshot>
+where
+    T: SommGravityExt + GravityConnection + ?Sized,
+{
+    if options.max_concurrency == 0 {
+        bail!("max_concurrency must be non-zero")
+    }
+
+    let captured_at = unix_now();
+    let sections: Vec<LocalBoxFuture<'_, (&'static str, Result<ExportSection>)>> = vec![
+        async { ("params", export_params(client).await) }.boxed_local(),
+        async { ("latest_signer_set", export_latest_signer_set(client).await) }.boxed_local(),
+        async { ("batches", export_batches(client).await) }.boxed_local(),
+        async { ("contract_calls", export_contract_calls(client).await) }.boxed_local(),
+        async { ("delegate_keys", export_delegate_keys(client).await) }.boxed_local(),
+    ];
+    let results = stream::iter(sections)
+        .buffer_unordered(options.max_concurrency)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut snapshot = GravityStateSnapshot {
+        endpoint: client.gravity_endpoint(),
+        captured_at,
+        ..Default::default()
+    };
+    for (section, result) in results {
+        match result {
+            Ok(ExportSection::Params(params)) => snapshot.params = Some(params),
+            Ok(ExportSection::LatestSignerSet(signer_set)) => snapshot.latest_signer_set = Some(signer_set),
+            Ok(ExportSection::Batches(batches)) => snapshot.batches = Some(batches),
+            Ok(ExportSection::ContractCalls(calls)) => snapshot.contract_calls = Some(calls),
+            Ok(ExportSection::DelegateKeys(keys)) => snapshot.delegate_keys = Some(keys),
+            Err(e) => snapshot.errors.push(SectionError {
+                section: section.to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+    snapshot.errors.sort_by(|a, b| a.section.cmp(&b.section));
+
+    Ok(snapshot)
+}
+
+#[cfg(feature = "query")]
+async fn export_params<T: SommGravityExt + ?Sized>(client: &T) -> Result<ExportSection> {
+    match client.query_somm_gravity_params().await?.params {
+        Some(params) => Ok(ExportSection::Params((&params).into())),
+        None => bail!("params response did not contain params"),
+    }
+}
+
+#[cfg(feature = "query")]
+async fn export_latest_signer_set<T: SommGravityExt + ?Sized>(client: &T) -> Result<ExportSection> {
+    match client.query_latest_signer_set_tx().await?.signer_set {
+        Some(signer_set) => Ok(ExportSection::LatestSignerSet((&signer_set).into())),
+        None => bail!("latest signer set response did not contain a signer set"),
+    }
+}
+
+#[cfg(feature = "query")]
+async fn export_batches<T: SommGravityExt + ?Sized>(client: &T) -> Result<ExportSection> {
+    let batches = client.query_all_batch_txs().await?;
+
+    Ok(ExportSection::Batches(batches.iter().map(Into::into).collect()))
+}
+
+#[cfg(feature = "query")]
+async fn export_contract_calls<T: SommGravityExt + ?Sized>(client: &T) -> Result<ExportSection> {
+    let calls = client.query_all_contract_call_txs().await?;
+
+    Ok(ExportSection::ContractCalls(calls.iter().map(Into::into).collect()))
+}
+
+#[cfg(feature = "query")]
+async fn export_delegate_keys<T: SommGravityExt + ?Sized>(client: &T) -> Result<ExportSection> {
+    let keys = client.query_delegate_keys().await?.delegate_keys;
+
+    Ok(ExportSection::DelegateKeys(keys.iter().map(Into::into).collect()))
+}
+
+/// Streams every section to `writer` as NDJSON, one query page at a time. Query failures are written as error
+/// records; only failures to write abort the export.
+#[cfg(feature = "query")]
+pub(crate) async fn export_state_ndjson<T, W>(client: &T, writer: &mut W) -> Result<()>
+where
+    T: SommGravityExt + GravityConnection,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let header = ExportHeader {
+        endpoint: client.gravity_endpoint(),
+        captured_at: unix_now(),
+    };
+    write_record(writer, &StateRecord::Header(header)).await?;
+
+    match client.query_somm_gravity_params().await.map(|r| r.params) {
+        Ok(Some(params)) => write_record(writer, &StateRecord::Params((&params).into())).await?,
+        Ok(None) => write_error(writer, "params", "params response did not contain params".to_string()).await?,
+        Err(e) => write_error(writer, "params", e.to_string()).await?,
+    }
+
+    match client.query_latest_signer_set_tx().await.map(|r| r.signer_set) {
+        Ok(Some(signer_set)) => {
+            write_record(writer, &StateRecord::LatestSignerSet((&signer_set).into())).await?
+        }
+        Ok(None) => {
+            let error = "latest signer set response did not contain a signer set".to_string();
+            write_error(writer, "latest_signer_set", error).await?
+        }
+        Err(e) => write_error(writer, "latest_signer_set", e.to_string()).await?,
+    }
+
+    ndjson_batches(client, writer).await?;
+    ndjson_contract_calls(client, writer).await?;
+
+    match client.query_delegate_keys().await {
+        Ok(response) => {
+            for keys in response.delegate_keys.iter() {
+                write_record(writer, &StateRecord::DelegateKeys(keys.into())).await?;
+            }
+        }
+        Err(e) => write_error(writer, "delegate_keys", e.to_string()).await?,
+    }
+
+    writer.flush().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "query")]
+async fn ndjson_batches<T, W>(client: &T, writer: &mut W) -> Result<()>
+where
+    T: SommGravityExt,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut next_key = Vec::new();
+    loop {
+        let pagination = PageRequest {
+            key: next_key,
+            ..Default::default()
+        };
+        let response = match client.query_batch_txs(Some(pagination)).await {
+            Ok(response) => response,
+            Err(e) => return write_error(writer, "batches", e.to_string()).await,
+        };
+
+        for batch in response.batches.iter() {
+            write_record(writer, &StateRecord::Batch(batch.into())).await?;
+            match client
+                .query_batch_tx_confirmations(batch.batch_nonce, &batch.token_contract)
+                .await
+            {
+                Ok(confirmations) => {
+                    for confirmation in confirmations.signatures.iter() {
+                        write_record(writer, &StateRecord::BatchConfirmation(confirmation.into())).await?;
+                    }
+                }
+                Err(e) => {
+                    let error = format!("batch {} nonce {}: {}", batch.token_contract, batch.batch_nonce, e);
+                    write_error(writer, "batch_confirmations", error).await?
+                }
+            }
+        }
+
+        match response.pagination {
+            Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "query")]
+async fn ndjson_contract_calls<T, W>(client: &T, writer: &mut W) -> Result<()>
+where
+    T: SommGravityExt + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut next_key = Vec::new();
+    loop {
+        let pagination = PageRequest {
+            key: next_key,
+            ..Default::default()
+        };
+        let response = match client.query_contract_call_txs(Some(pagination)).await {
+            Ok(response) => response,
+            Err(e) => return write_error(writer, "contract_calls", e.to_string()).await,
+        };
+
+        for call in response.calls.iter() {
+            write_record(writer, &StateRecord::ContractCall(call.into())).await?;
+            match client
+                .query_contract_call_tx_confirmations(call.invalidation_scope.clone(), call.invalidation_nonce)
+                .await
+            {
+                Ok(confirmations) => {
+                    for confirmation in confirmations.signatures.iter() {
+                        write_record(writer, &StateRecord::ContractCallConfirmation(confirmation.into())).await?;
+                    }
+                }
+                Err(e) => {
+                    let error = format!(
+                        "contract call scope 0x{} nonce {}: {}",
+                        hex::encode(&call.invalidation_scope),
+                        call.invalidation_nonce,
+                        e
+                    );
+                    write_error(writer, "contract_call_confirmations", error).await?
+                }
+            }
+        }
+
+        match response.pagination {
+            Some(page) if !page.next_key.is_empty() => next_key = page.next_key,
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "query")]
+async fn write_record<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W, record: &StateRecord) -> Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "query")]
+async fn write_error<W: AsyncWrite + Unpin + ?Sized>(writer: &mut W, section: &str, error: String) -> Result<()> {
+    let error = SectionError {
+        section: section.to_string(),
+        error,
+    };
+
+    write_record(writer, &StateRecord::Error(error)).await
+}
+
+#[cfg(feature = "query")]
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
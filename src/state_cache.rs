@@ -0,0 +1,126 @@
+//! A background refreshed cache of the delegate keys and latest signer set, for monitors that read them far
+//! more often than they change.
+//!
+//! [`StateCache::spawn`] starts a task on the current [`LocalSet`](tokio::task::LocalSet), since the query
+//! futures are not `Send`, that refreshes both on an interval. Reads never touch the network and return
+//! whatever the last successful refresh fetched:
+//!
+//! ```ignore
+//! let local = tokio::task::LocalSet::new();
+//! local.run_until(async {
+//!     let cache = StateCache::spawn(session, Duration::from_secs(30))?;
+//!     // ...
+//!     if let Some(signer_set) = cache.latest_signer_set() {
+//!         println!("signer set {} as of {:?} ago", signer_set.nonce, cache.age());
+//!     }
+//! }).await;
+//! ```
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use futures::join;
+use gravity_proto::gravity::{MsgDelegateKeys, SignerSetTx};
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+
+use crate::{bail, extension::SommGravityExt, Result};
+
+/// A handle to the cached state. Clones share the same cache and the refresh task is stopped once every handle
+/// has been dropped.
+#[derive(Clone, Debug)]
+pub struct StateCache {
+    state: Arc<RwLock<CachedState>>,
+    _task: Arc<AbortOnDrop>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct CachedState {
+    delegate_keys: Option<Vec<MsgDelegateKeys>>,
+    latest_signer_set: Option<SignerSetTx>,
+    last_refresh: Option<Instant>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl StateCache {
+    /// Spawns a task that refreshes the cache from `client` immediately and then every `refresh_interval`.
+    /// Must be called from within a [`LocalSet`](tokio::task::LocalSet). Fails if `refresh_interval` is zero.
+    pub fn spawn<T: SommGravityExt + 'static>(client: T, refresh_interval: Duration) -> Result<Self> {
+        if refresh_interval.is_zero() {
+            bail!("refresh_interval must be non-zero");
+        }
+
+        let state = Arc::new(RwLock::new(CachedState::default()));
+        let task_state = state.clone();
+        let task = tokio::task::spawn_local(async move {
+            let mut ticks = interval(refresh_interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                refresh(&client, &task_state).await;
+            }
+        });
+
+        Ok(Self {
+            state,
+            _task: Arc::new(AbortOnDrop(task)),
+        })
+    }
+
+    /// The delegate keys as of the last successful refresh, or `None` before the first
+    pub fn delegate_keys(&self) -> Option<Vec<MsgDelegateKeys>> {
+        self.read().delegate_keys.clone()
+    }
+
+    /// The latest signer set as of the last successful refresh, or `None` before the first
+    pub fn latest_signer_set(&self) -> Option<SignerSetTx> {
+        self.read().latest_signer_set.clone()
+    }
+
+    /// When both values were last refreshed successfully
+    pub fn last_refresh(&self) -> Option<Instant> {
+        self.read().last_refresh
+    }
+
+    /// Time since the last successful refresh, or `None` before the first
+    pub fn age(&self) -> Option<Duration> {
+        self.last_refresh().map(|at| at.elapsed())
+    }
+
+    /// The error of the most recent refresh if it failed, cleared by the next successful one. A failed refresh
+    /// keeps the previously cached values.
+    pub fn last_error(&self) -> Option<String> {
+        self.read().last_error.clone()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, CachedState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+async fn refresh<T: SommGravityExt>(client: &T, state: &RwLock<CachedState>) {
+    let (delegate_keys, signer_set) = join!(client.query_delegate_keys(), client.query_latest_signer_set_tx());
+    let mut state = state.write().unwrap_or_else(|e| e.into_inner());
+    match (delegate_keys, signer_set.map(|r| r.signer_set)) {
+        (Ok(delegate_keys), Ok(Some(signer_set))) => {
+            state.delegate_keys = Some(delegate_keys.delegate_keys);
+            state.latest_signer_set = Some(signer_set);
+            state.last_refresh = Some(Instant::now());
+            state.last_error = None;
+        }
+        (Ok(_), Ok(None)) => state.last_error = Some("latest signer set response was empty".to_string()),
+        (Err(e), _) | (_, Err(e)) => state.last_error = Some(e.to_string()),
+    }
+}
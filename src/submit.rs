@@ -0,0 +1,411 @@
+//! Building, signing and broadcasting a gravity message in a single call.
+//!
+//! [`SommGravitySubmit::submit`] wraps a [`SommGravity`] message in a tx, wrapping the three confirmation
+//! variants in a `MsgSubmitEthereumTxConfirmation` from the signer first since they cannot be submitted on
+//! their own, then signs it with `ocular` and broadcasts it in commit mode, which returns once the tx is in a
+//! block:
+//!
+//! ```ignore
+//! let result = grpc_client
+//!     .submit(msg, &orchestrator, FeeInfo::new(fee), &chain_context)
+//!     .await?;
+//! println!("send {:?} included at height {}", result.send_ids(), result.response.height);
+//! ```
+//!
+//! The returned [`SubmissionResult`] holds the raw response alongside the ids and nonces the gravity module
+//! reported in the tx's events, such as the id assigned to a new send.
+//!
+//! Broadcasting is not idempotent, so retrying a submit after a timeout can send the same transfer twice.
+//! [`SommGravitySubmit::submit_once`] takes a caller chosen key and returns the earlier response for a key
+//! already submitted through the same [`SubmitGuard`].
+//!
+//! [`SommGravitySubmit::submit_with_observer`] and [`SommGravitySubmit::submit_once_with_observer`] report a
+//! confirmation to a [`ConfirmationObserver`] once its tx is built and again once it is included, completing the
+//! events reported while signing by [`sign_batch_confirmation_with_observer`](crate::signature::sign_batch_confirmation_with_observer)
+//! and its siblings.
+//!
+//! Callers that need more than one message in a tx or their own broadcast mode should build the tx with
+//! [`ModuleMsg::into_tx`] and sign it themselves.
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use ocular::{
+    account::AccountInfo,
+    chain::ChainContext,
+    grpc::GrpcClient,
+    tx::{FeeInfo, ModuleMsg, Response, UnsignedTx},
+};
+
+use crate::{
+    bail,
+    confirmations::{
+        classify_confirmation, decode_batch_confirmation, decode_contract_call_confirmation,
+        decode_signer_set_confirmation,
+    },
+    error::TxFailed,
+    msgs::SommGravity,
+    observer::{ConfirmationKind, ConfirmationObserver, NoopObserver},
+    Error, Result,
+};
+
+/// Single call submission of gravity messages, implemented for [`GrpcClient`]
+#[async_trait(?Send)]
+pub trait SommGravitySubmit {
+    /// Signs `msg` with `signer`, paying `fee`, broadcasts it and waits for it to be included in a block.
+    /// Confirmation variants are wrapped in a `MsgSubmitEthereumTxConfirmation` signed by `signer`'s address
+    /// under `chain_context`'s prefix. A tx rejected by `CheckTx` or failing in its block is returned as a
+    /// [`TxFailed`] error.
+    async fn submit(
+        &mut self,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+    ) -> Result<SubmissionResult>;
+    /// Same as [`SommGravitySubmit::submit`], unless `key` was already submitted successfully through `guard`
+    /// within its TTL, in which case the earlier result is returned without broadcasting.
+    async fn submit_once(
+        &mut self,
+        guard: &SubmitGuard,
+        key: &str,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+    ) -> Result<SubmissionResult>;
+    /// Same as [`SommGravitySubmit::submit`], reporting a confirmation to `observer` as queued once its tx is
+    /// built and as submitted once the tx is included. Other messages report nothing.
+    async fn submit_with_observer(
+        &mut self,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+        observer: &dyn ConfirmationObserver,
+    ) -> Result<SubmissionResult>;
+    /// Same as [`SommGravitySubmit::submit_once`], reporting to `observer` as
+    /// [`SommGravitySubmit::submit_with_observer`] does. Nothing is reported when an earlier result is returned.
+    #[allow(clippy::too_many_arguments)]
+    async fn submit_once_with_observer(
+        &mut self,
+        guard: &SubmitGuard,
+        key: &str,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+        observer: &dyn ConfirmationObserver,
+    ) -> Result<SubmissionResult>;
+}
+
+#[async_trait(?Send)]
+impl SommGravitySubmit for GrpcClient {
+    async fn submit(
+        &mut self,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+    ) -> Result<SubmissionResult> {
+        self.submit_with_observer(msg, signer, fee, chain_context, &NoopObserver).await
+    }
+
+    async fn submit_once(
+        &mut self,
+        guard: &SubmitGuard,
+        key: &str,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+    ) -> Result<SubmissionResult> {
+        self.submit_once_with_observer(guard, key, msg, signer, fee, chain_context, &NoopObserver)
+            .await
+    }
+
+    async fn submit_with_observer(
+        &mut self,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+        observer: &dyn ConfirmationObserver,
+    ) -> Result<SubmissionResult> {
+        let address = match signer.address(&chain_context.prefix) {
+            Ok(address) => address,
+            Err(e) => bail!("failed to derive signer address: {}", e),
+        };
+        let confirmation = confirmation_id(&msg);
+        let tx = build_tx(msg, &address)?;
+        if let Some((kind, nonce)) = confirmation {
+            observer.queued(kind, nonce);
+        }
+        let signed = match tx.sign(signer, fee, chain_context, self).await {
+            Ok(signed) => signed,
+            Err(e) => bail!("failed to sign tx: {}", e),
+        };
+        let response = match signed.broadcast_commit(self).await {
+            Ok(response) => response,
+            Err(e) => bail!("failed to broadcast tx: {}", e),
+        };
+
+        let tx_hash = response.hash.to_string();
+        if response.check_tx.code.is_err() {
+            return Err(Error::new(TxFailed {
+                tx_hash,
+                phase: "check_tx",
+                code: response.check_tx.code.value(),
+                log: response.check_tx.log.to_string(),
+            }));
+        }
+        if response.deliver_tx.code.is_err() {
+            return Err(Error::new(TxFailed {
+                tx_hash,
+                phase: "deliver_tx",
+                code: response.deliver_tx.code.value(),
+                log: response.deliver_tx.log.to_string(),
+            }));
+        }
+        if let Some((kind, nonce)) = confirmation {
+            observer.submitted(kind, nonce, &tx_hash);
+        }
+
+        Ok(SubmissionResult {
+            events: gravity_events(&response),
+            response,
+        })
+    }
+
+    async fn submit_once_with_observer(
+        &mut self,
+        guard: &SubmitGuard,
+        key: &str,
+        msg: SommGravity<'_>,
+        signer: &AccountInfo,
+        fee: FeeInfo,
+        chain_context: &ChainContext,
+        observer: &dyn ConfirmationObserver,
+    ) -> Result<SubmissionResult> {
+        if let Some(result) = guard.get(key) {
+            return Ok(result);
+        }
+
+        let result = self
+            .submit_with_observer(msg, signer, fee, chain_context, observer)
+            .await?;
+        guard.insert(key, result.clone());
+
+        Ok(result)
+    }
+}
+
+/// Remembers the results of recent [`SommGravitySubmit::submit_once`] calls by key, for `ttl` after each
+/// submission.
+///
+/// The guard is best effort and only covers submissions made through it within this process. Keys are
+/// recorded once a tx is included, so two concurrent calls with the same key can both broadcast, and a call
+/// that failed after its tx reached the mempool, such as one that timed out waiting for inclusion, is not
+/// recorded and will broadcast again when retried.
+#[derive(Debug)]
+pub struct SubmitGuard {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, SubmissionResult)>>,
+}
+
+impl SubmitGuard {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the result recorded for `key` if it hasn't expired
+    pub fn get(&self, key: &str) -> Option<SubmissionResult> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        self.prune(&mut entries);
+
+        entries.get(key).map(|(_, result)| result.clone())
+    }
+
+    fn insert(&self, key: &str, result: SubmissionResult) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        self.prune(&mut entries);
+        entries.insert(key.to_string(), (Instant::now(), result));
+    }
+
+    fn prune(&self, entries: &mut HashMap<String, (Instant, SubmissionResult)>) {
+        entries.retain(|_, (submitted_at, _)| submitted_at.elapsed() < self.ttl);
+    }
+}
+
+/// A successfully included tx, as returned by [`SommGravitySubmit::submit`]
+#[derive(Clone, Debug)]
+pub struct SubmissionResult {
+    /// The node's full broadcast response
+    pub response: Response,
+    /// The gravity module's events from the tx, in the order emitted. Empty for messages that emit none, such as
+    /// confirmations.
+    pub events: Vec<GravityTxEvent>,
+}
+
+impl SubmissionResult {
+    /// Ids of the sends created or cancelled by the tx
+    pub fn send_ids(&self) -> Vec<u64> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                GravityTxEvent::OutgoingTxId(id) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Nonces of the batches created by the tx
+    pub fn batch_nonces(&self) -> Vec<u64> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                GravityTxEvent::BatchNonce(nonce) => Some(*nonce),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// An id or nonce the gravity module reports in a tx's `message` events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GravityTxEvent {
+    /// `outgoing_tx_id`: the id assigned by `MsgSendToEthereum`, or the id cancelled by
+    /// `MsgCancelSendToEthereum`
+    OutgoingTxId(u64),
+    /// `batch_nonce`: the nonce of the batch created by `MsgRequestBatchTx`
+    BatchNonce(u64),
+    /// `signerset_nonce`: a signer set nonce
+    SignerSetNonce(u64),
+}
+
+/// Extracts the gravity attributes from the `message` events the module tags with `module=gravity`. Attributes
+/// whose values aren't integers are skipped.
+fn gravity_events(response: &Response) -> Vec<GravityTxEvent> {
+    let mut events = Vec::new();
+    for event in response.deliver_tx.events.iter() {
+        if event.kind != "message" {
+            continue;
+        }
+        let attributes = event
+            .attributes
+            .iter()
+            .map(|a| (a.key.to_string(), a.value.to_string()))
+            .collect::<Vec<(String, String)>>();
+        if !attributes.iter().any(|(k, v)| k == "module" && v == "gravity") {
+            continue;
+        }
+
+        for (key, value) in attributes {
+            let value = match value.parse::<u64>() {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            match key.as_str() {
+                "outgoing_tx_id" => events.push(GravityTxEvent::OutgoingTxId(value)),
+                "batch_nonce" => events.push(GravityTxEvent::BatchNonce(value)),
+                "signerset_nonce" => events.push(GravityTxEvent::SignerSetNonce(value)),
+                _ => {}
+            }
+        }
+    }
+
+    events
+}
+
+/// Returns the kind and nonce of the confirmation `msg` submits, or `None` for any other message. A wrapped
+/// confirmation that fails to decode is treated as any other message, leaving the error to encoding.
+fn confirmation_id(msg: &SommGravity<'_>) -> Option<(ConfirmationKind, u64)> {
+    match msg {
+        SommGravity::BatchTxConfirmation { batch_nonce, .. } => Some((ConfirmationKind::Batch, *batch_nonce)),
+        SommGravity::SignerSetTxConfirmation { signer_set_nonce, .. } => {
+            Some((ConfirmationKind::SignerSet, *signer_set_nonce))
+        }
+        SommGravity::ContractCallTxConfirmation { invalidation_nonce, .. } => {
+            Some((ConfirmationKind::ContractCall, *invalidation_nonce))
+        }
+        SommGravity::SubmitEthereumTxConfirmation { confirmation, .. } => {
+            let kind = classify_confirmation(confirmation).ok()?;
+            let nonce = match kind {
+                ConfirmationKind::Batch => decode_batch_confirmation(confirmation).ok()?.batch_nonce,
+                ConfirmationKind::SignerSet => decode_signer_set_confirmation(confirmation).ok()?.signer_set_nonce,
+                ConfirmationKind::ContractCall => {
+                    decode_contract_call_confirmation(confirmation).ok()?.invalidation_nonce
+                }
+            };
+            Some((kind, nonce))
+        }
+        _ => None,
+    }
+}
+
+/// Converts `msg` into a tx, first wrapping confirmations in a `MsgSubmitEthereumTxConfirmation` from `signer`
+fn build_tx(msg: SommGravity<'_>, signer: &str) -> Result<UnsignedTx> {
+    match msg {
+        SommGravity::ContractCallTxConfirmation { .. }
+        | SommGravity::BatchTxConfirmation { .. }
+        | SommGravity::SignerSetTxConfirmation { .. } => SommGravity::SubmitEthereumTxConfirmation {
+            confirmation: msg.into_any()?,
+            signer,
+        }
+        .into_tx(),
+        _ => msg.into_tx(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNER: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+
+    fn batch_confirmation() -> SommGravity<'static> {
+        SommGravity::BatchTxConfirmation {
+            token_contract_address: "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            batch_nonce: 7,
+            ethereum_signer: SIGNER,
+            signature: vec![0u8; 65],
+        }
+    }
+
+    #[test]
+    fn confirmation_id_of_confirmations() {
+        assert_eq!(confirmation_id(&batch_confirmation()), Some((ConfirmationKind::Batch, 7)));
+        assert_eq!(
+            confirmation_id(&SommGravity::SignerSetTxConfirmation {
+                signer_set_nonce: 3,
+                ethereum_signer: SIGNER,
+                signature: vec![0u8; 65],
+            }),
+            Some((ConfirmationKind::SignerSet, 3))
+        );
+    }
+
+    #[test]
+    fn confirmation_id_of_wrapped_confirmation() {
+        let wrapped = SommGravity::SubmitEthereumTxConfirmation {
+            confirmation: batch_confirmation().into_any().unwrap(),
+            signer: "somm1",
+        };
+        assert_eq!(confirmation_id(&wrapped), Some((ConfirmationKind::Batch, 7)));
+    }
+
+    #[test]
+    fn confirmation_id_of_other_messages() {
+        let msg = SommGravity::RequestBatchTx {
+            denom: "gravity0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+            signer: "somm1",
+        };
+        assert_eq!(confirmation_id(&msg), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Protocol level logging of the raw query requests and responses, for debugging field mismatches against a
+//! node. With the `wire_log` feature each message is logged at trace level under the
+//! `ocular_somm_gravity::wire` target, as the hex of its protobuf encoding alongside its `Debug` form.
+//! Signatures are zeroed before logging unless the [`RedactionPolicy`](crate::display::RedactionPolicy) is
+//! `Full`. Without the feature the hooks compile to nothing.
+#[cfg(feature = "wire_log")]
+use std::fmt;
+
+#[cfg(feature = "wire_log")]
+use gravity_proto::gravity::*;
+#[cfg(feature = "wire_log")]
+use prost::Message;
+
+#[cfg(feature = "wire_log")]
+use crate::display::{redaction_policy, RedactionPolicy};
+
+/// Logs a request before it is sent
+#[cfg(feature = "wire_log")]
+pub(crate) fn request<M: WireMessage>(method: &str, message: &M) {
+    log(method, "request", message)
+}
+
+#[cfg(not(feature = "wire_log"))]
+#[inline(always)]
+pub(crate) fn request<M>(_method: &str, _message: &M) {}
+
+/// Logs a successfully decoded response
+#[cfg(feature = "wire_log")]
+pub(crate) fn response<M: WireMessage>(method: &str, message: &M) {
+    log(method, "response", message)
+}
+
+#[cfg(not(feature = "wire_log"))]
+#[inline(always)]
+pub(crate) fn response<M>(_method: &str, _message: &M) {}
+
+#[cfg(feature = "wire_log")]
+fn log<M: WireMessage>(method: &str, direction: &str, message: &M) {
+    if !tracing::enabled!(target: "ocular_somm_gravity::wire", tracing::Level::TRACE) {
+        return;
+    }
+
+    let redacted;
+    let message = match redaction_policy() {
+        RedactionPolicy::Full => message,
+        RedactionPolicy::Redacted => {
+            redacted = message.redacted();
+            &redacted
+        }
+    };
+    tracing::trace!(
+        target: "ocular_somm_gravity::wire",
+        method,
+        direction,
+        bytes = %hex::encode(message.encode_to_vec()),
+        "{:?}",
+        message
+    );
+}
+
+/// A query message that can be logged. Messages carrying signatures override `redacted` to zero them.
+#[cfg(feature = "wire_log")]
+pub(crate) trait WireMessage: Message + fmt::Debug + Clone {
+    fn redacted(&self) -> Self {
+        self.clone()
+    }
+}
+
+#[cfg(feature = "wire_log")]
+macro_rules! impl_wire_message {
+    ($($message:ty),* $(,)?) => {
+        $(impl WireMessage for $message {})*
+    };
+}
+
+#[cfg(feature = "wire_log")]
+impl_wire_message!(
+    ParamsRequest,
+    ParamsResponse,
+    SignerSetTxRequest,
+    LatestSignerSetTxRequest,
+    SignerSetTxResponse,
+    BatchTxRequest,
+    BatchTxResponse,
+    ContractCallTxRequest,
+    ContractCallTxResponse,
+    SignerSetTxsRequest,
+    SignerSetTxsResponse,
+    BatchTxsRequest,
+    BatchTxsResponse,
+    ContractCallTxsRequest,
+    ContractCallTxsResponse,
+    SignerSetTxConfirmationsRequest,
+    BatchTxConfirmationsRequest,
+    ContractCallTxConfirmationsRequest,
+    UnsignedSignerSetTxsRequest,
+    UnsignedSignerSetTxsResponse,
+    UnsignedBatchTxsRequest,
+    UnsignedBatchTxsResponse,
+    UnsignedContractCallTxsRequest,
+    UnsignedContractCallTxsResponse,
+    LastSubmittedEthereumEventRequest,
+    LastSubmittedEthereumEventResponse,
+    Erc20ToDenomRequest,
+    Erc20ToDenomResponse,
+    DenomToErc20ParamsRequest,
+    DenomToErc20ParamsResponse,
+    DenomToErc20Request,
+    DenomToErc20Response,
+    DelegateKeysByValidatorRequest,
+    DelegateKeysByValidatorResponse,
+    DelegateKeysByEthereumSignerRequest,
+    DelegateKeysByEthereumSignerResponse,
+    DelegateKeysByOrchestratorRequest,
+    DelegateKeysByOrchestratorResponse,
+    DelegateKeysRequest,
+    BatchedSendToEthereumsRequest,
+    BatchedSendToEthereumsResponse,
+    UnbatchedSendToEthereumsRequest,
+    UnbatchedSendToEthereumsResponse,
+);
+
+#[cfg(feature = "wire_log")]
+impl WireMessage for SignerSetTxConfirmationsResponse {
+    fn redacted(&self) -> Self {
+        let mut response = self.clone();
+        for confirmation in response.signatures.iter_mut() {
+            zero(&mut confirmation.signature);
+        }
+
+        response
+    }
+}
+
+#[cfg(feature = "wire_log")]
+impl WireMessage for BatchTxConfirmationsResponse {
+    fn redacted(&self) -> Self {
+        let mut response = self.clone();
+        for confirmation in response.signatures.iter_mut() {
+            zero(&mut confirmation.signature);
+        }
+
+        response
+    }
+}
+
+#[cfg(feature = "wire_log")]
+impl WireMessage for ContractCallTxConfirmationsResponse {
+    fn redacted(&self) -> Self {
+        let mut response = self.clone();
+        for confirmation in response.signatures.iter_mut() {
+            zero(&mut confirmation.signature);
+        }
+
+        response
+    }
+}
+
+#[cfg(feature = "wire_log")]
+impl WireMessage for DelegateKeysResponse {
+    fn redacted(&self) -> Self {
+        let mut response = self.clone();
+        for keys in response.delegate_keys.iter_mut() {
+            zero(&mut keys.eth_signature);
+        }
+
+        response
+    }
+}
+
+/// Zeroes signature bytes in place, keeping their length visible in the encoding
+#[cfg(feature = "wire_log")]
+fn zero(bytes: &mut [u8]) {
+    bytes.iter_mut().for_each(|b| *b = 0);
+}